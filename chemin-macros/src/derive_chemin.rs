@@ -1,31 +1,120 @@
 mod router;
 use router::*;
+mod available_locales_generation;
 mod generate_url_generation;
 mod generate_url_parsing;
+mod routes_generation;
+mod unknown_query_generation;
 
-use proc_macro2::TokenStream;
+use proc_macro2::{Span, TokenStream};
 use quote::quote;
+use syn::{Fields, Ident, Index, Member};
 
 pub fn derive_chemin(item: TokenStream, chemin_crate: &TokenStream) -> TokenStream {
-    let Router { item_enum, routes } = match Router::parse(item) {
+    let Router {
+        item_enum,
+        options,
+        routes,
+    } = match Router::parse(item) {
         Ok(router) => router,
         Err(error) => return error.into_compile_error(),
     };
 
     let enum_ident = &item_enum.ident;
     let (impl_generics, ty_generics, where_clause) = item_enum.generics.split_for_impl();
-    let parsing_method = generate_url_parsing::parsing_method(&routes, chemin_crate);
+    let parsing_method =
+        generate_url_parsing::parsing_method(enum_ident, &routes, &options, chemin_crate);
     let url_generation_method =
-        generate_url_generation::url_generation_method(&routes, chemin_crate);
+        generate_url_generation::url_generation_method(enum_ident, &routes, &options, chemin_crate);
+    let available_locales_method =
+        available_locales_generation::available_locales_method(&routes, chemin_crate);
+    let routes_const = routes_generation::routes_const(&routes, &options, chemin_crate);
+    let unknown_query_items =
+        unknown_query_generation::unknown_query_items(&routes, &options, chemin_crate);
+    let query_key_checks = if item_enum.generics.params.is_empty() {
+        routes_generation::query_key_checks(enum_ident, &routes, chemin_crate)
+    } else {
+        TokenStream::new()
+    };
 
     quote!(
         impl #impl_generics #chemin_crate::Chemin for #enum_ident #ty_generics #where_clause {
+            #routes_const
             #parsing_method
             #url_generation_method
+            #available_locales_method
+            #unknown_query_items
         }
+
+        #query_key_checks
     )
 }
 
 fn unnamed_param_name(i: usize) -> String {
     format!("p{}", i)
 }
+
+fn route_variant(enum_ident: &Ident, route: &Route, chemin_crate: &TokenStream) -> TokenStream {
+    let variant_ident = &route.variant.ident;
+    quote!(#chemin_crate::RouteVariant {
+        enum_name: ::std::stringify!(#enum_ident),
+        variant: ::std::stringify!(#variant_ident),
+    })
+}
+
+/// A `&'static Constraint` expression.
+fn constraint(constraint: &str, chemin_crate: &TokenStream) -> TokenStream {
+    quote!({
+        static CONSTRAINT: #chemin_crate::Constraint = #chemin_crate::Constraint::new(#constraint);
+        &CONSTRAINT
+    })
+}
+
+/// A `TrailingSlash` expression.
+fn trailing_slash(trailing_slash: TrailingSlash, chemin_crate: &TokenStream) -> TokenStream {
+    match trailing_slash {
+        TrailingSlash::Strict => quote!(#chemin_crate::TrailingSlash::Strict),
+        TrailingSlash::Ignore => quote!(#chemin_crate::TrailingSlash::Ignore),
+        TrailingSlash::Redirect => quote!(#chemin_crate::TrailingSlash::Redirect),
+    }
+}
+
+/// An `Option<&'static str>` expression.
+fn option_str(value: Option<&str>) -> TokenStream {
+    match value {
+        Some(value) => quote!(::std::option::Option::Some(#value)),
+        None => quote!(::std::option::Option::None),
+    }
+}
+
+fn route_locales(localized_route: &LocalizedRoute, chemin_crate: &TokenStream) -> TokenStream {
+    if localized_route.locales.is_empty() {
+        quote!(#chemin_crate::RouteLocales::Any)
+    } else {
+        let route_locales = localized_route.locales.iter();
+        quote!(#chemin_crate::RouteLocales::Some(&[#(#route_locales),*]))
+    }
+}
+
+fn sub_route_member(route: &Route, localized_route: &LocalizedRoute) -> Option<Member> {
+    match localized_route.path.sub_route.as_ref()? {
+        SubRoute::Unnamed => match route.variant.fields {
+            // The unnamed sub-route is always the last field.
+            Fields::Unnamed(_) => Some(Member::Unnamed(Index {
+                index: localized_route.path.params().count() as u32,
+                span: Span::call_site(),
+            })),
+            Fields::Named(_) | Fields::Unit => unreachable!(),
+        },
+
+        SubRoute::Named(name) => Some(Member::Named(Ident::new(name, Span::call_site()))),
+    }
+}
+
+/// All the localized routes of a variant have the same sub-route field.
+fn route_sub_route_member(route: &Route) -> Option<Member> {
+    route
+        .localized_routes
+        .first()
+        .and_then(|localized_route| sub_route_member(route, localized_route))
+}
@@ -0,0 +1,67 @@
+use super::router::*;
+use super::{route_locales, sub_route_member};
+use proc_macro2::TokenStream;
+use quote::quote;
+
+pub fn available_locales_method(routes: &[Route], chemin_crate: &TokenStream) -> TokenStream {
+    let route_match_arms = routes
+        .iter()
+        .map(|route| route_match_arm(route, chemin_crate));
+
+    quote!(
+        fn available_locales_with_accepted_locales(
+            &self,
+            accepted_locales: &#chemin_crate::AcceptedLocales,
+        ) -> ::std::vec::Vec<#chemin_crate::Locale> {
+            let mut available_locales = ::std::vec::Vec::new();
+
+            match self {
+                #(#route_match_arms)*
+                _ => ::std::unreachable!(),
+            }
+
+            available_locales
+        }
+    )
+}
+
+fn route_match_arm(route: &Route, chemin_crate: &TokenStream) -> TokenStream {
+    let variant_ident = &route.variant.ident;
+
+    // All the localized routes of a variant have the same sub-route field.
+    let route_variant_pat = match route
+        .localized_routes
+        .first()
+        .and_then(|localized_route| sub_route_member(route, localized_route))
+    {
+        Some(sub_route_member) => quote!(Self::#variant_ident { #sub_route_member: sub_route, .. }),
+        None => quote!(Self::#variant_ident { .. }),
+    };
+
+    let localized_routes_locales = route.localized_routes.iter().map(|localized_route| {
+        let route_locales = route_locales(localized_route, chemin_crate);
+
+        let locales_adding = if localized_route.path.sub_route.is_some() {
+            quote!(available_locales.extend(
+                #chemin_crate::Chemin::available_locales_with_accepted_locales(
+                    sub_route,
+                    &accepted_locales.accepted_locales_for_sub_route(&ROUTE_LOCALES),
+                )
+            ))
+        } else {
+            quote!(available_locales.extend(accepted_locales.resulting_locales(&ROUTE_LOCALES)))
+        };
+
+        quote!({
+            static ROUTE_LOCALES: #chemin_crate::RouteLocales = #route_locales;
+
+            if accepted_locales.accept(&ROUTE_LOCALES) {
+                #locales_adding;
+            }
+        })
+    });
+
+    quote!(#route_variant_pat => {
+        #(#localized_routes_locales)*
+    })
+}
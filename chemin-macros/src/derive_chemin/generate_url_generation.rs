@@ -1,76 +1,218 @@
 use super::router::*;
-use super::unnamed_param_name;
+use super::{constraint, option_str, route_sub_route_member, route_variant, unnamed_param_name};
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, quote_spanned};
-use syn::{Fields, Ident};
+use syn::{Fields, Ident, Member};
 
-pub fn url_generation_method(routes: &[Route], chemin_crate: &TokenStream) -> TokenStream {
+pub fn url_generation_method(
+    enum_ident: &Ident,
+    routes: &[Route],
+    options: &RouterOptions,
+    chemin_crate: &TokenStream,
+) -> TokenStream {
     let route_match_arms = routes
         .iter()
-        .map(|route| route_match_arm(route, chemin_crate));
+        .map(|route| route_match_arm(enum_ident, route, chemin_crate));
+
+    let query_order = match options.query_order {
+        QueryOrder::Tree => quote!(#chemin_crate::QueryOrder::Tree),
+        QueryOrder::Declaration => quote!(#chemin_crate::QueryOrder::Declaration),
+        QueryOrder::Alphabetical => quote!(#chemin_crate::QueryOrder::Alphabetical),
+    };
+
+    // The query order is only given to the sub-routes, used to move their query string parameters, and used to sort the values
+    // joined with a separator.
+    let query_order_param = if routes.iter().any(|route| {
+        route_sub_route_member(route).is_some()
+            || route
+                .query_params
+                .iter()
+                .any(|query_param| matches!(query_param.kind, QueryParamKind::Multiple(Some(_))))
+    }) {
+        quote!(__chemin_query_order)
+    } else {
+        quote!(_)
+    };
 
     quote!(
+        const QUERY_ORDER: #chemin_crate::QueryOrder = #query_order;
+
         fn generate_url_and_build_qstring(
             &self,
             __chemin_locale: ::std::option::Option<&::std::primitive::str>,
             __chemin_encode_params: ::std::primitive::bool,
+            #query_order_param: #chemin_crate::QueryOrder,
             __chemin_qstring: &mut #chemin_crate::deps::qstring::QString,
-        ) -> ::std::option::Option<::std::string::String> {
+        ) -> ::std::result::Result<::std::string::String, #chemin_crate::GenerateError> {
             match self {
                 #(#route_match_arms),*
-                _ => ::std::option::Option::None,
+                _ => ::std::unreachable!(),
             }
         }
     )
 }
 
-fn route_match_arm(route: &Route, chemin_crate: &TokenStream) -> TokenStream {
+fn route_match_arm(enum_ident: &Ident, route: &Route, chemin_crate: &TokenStream) -> TokenStream {
     let route_variant_pat = route_variant_pat(route);
-    let locale_match_arms = route
-        .localized_routes
-        .iter()
-        .map(|localized_route| locale_match_arm(localized_route, chemin_crate));
+    let route_variant = route_variant(enum_ident, route, chemin_crate);
+    let locale_match_arms = route.localized_routes.iter().map(|localized_route| {
+        locale_match_arm(route, localized_route, &route_variant, chemin_crate)
+    });
 
-    let qstring_pairs_adding = route
-        .query_params
-        .iter()
-        .map(|query_param| match query_param {
-            QueryParam::Mandatory(field_ident) => quote_spanned!(field_ident.span()=>
-                __chemin_qstring.add_pair((
-                    ::std::stringify!(#field_ident),
-                    ::std::string::ToString::to_string(&#field_ident)
-                ));
+    let format_fn = |field_ident: &Ident| match route.param_module(&field_ident.to_string()) {
+        Some(module) => quote!(#module::format),
+        None => quote!(::std::string::ToString::to_string),
+    };
+
+    let query_param_adding = |query_param: &QueryParam| {
+        let field_ident = &query_param.field_ident;
+        let key = &query_param.key;
+        let format_fn = format_fn(field_ident);
+
+        match &query_param.kind {
+            QueryParamKind::Mandatory => quote_spanned!(field_ident.span()=>
+                __chemin_qstring.add_pair((#key, #format_fn(#field_ident)));
             ),
 
-            QueryParam::Optional(field_ident) => quote_spanned!(field_ident.span()=>
+            QueryParamKind::Optional => quote_spanned!(field_ident.span()=>
                 if let ::std::option::Option::Some(value) = #field_ident {
-                    __chemin_qstring.add_pair((
-                        ::std::stringify!(#field_ident),
-                        ::std::string::ToString::to_string(&value)
-                    ))
+                    __chemin_qstring.add_pair((#key, #format_fn(value)))
                 }
             ),
 
-            QueryParam::WithDefaultValue(field_ident, default_value) => {
+            QueryParamKind::WithDefaultValue(default_value) => {
                 quote_spanned!(field_ident.span()=>
                     if #field_ident != &#default_value {
-                        __chemin_qstring.add_pair((
-                            ::std::stringify!(#field_ident),
-                            ::std::string::ToString::to_string(&#field_ident)
+                        __chemin_qstring.add_pair((#key, #format_fn(#field_ident)));
+                    }
+                )
+            }
+
+            QueryParamKind::Multiple(separator) => {
+                let field_name = field_ident.to_string();
+                let separator_str = separator.as_deref().unwrap_or_default();
+                let query_order = match separator {
+                    Some(_) => quote!(__chemin_query_order),
+                    None => quote!(#chemin_crate::QueryOrder::Tree),
+                };
+                let separator = option_str(separator.as_deref());
+
+                quote_spanned!(field_ident.span()=>
+                    if let ::std::result::Result::Err(value) = #chemin_crate::add_query_param_values(
+                        __chemin_qstring,
+                        #key,
+                        ::std::iter::Iterator::map(::std::iter::IntoIterator::into_iter(#field_ident), #format_fn),
+                        #separator,
+                        #query_order,
+                    ) {
+                        return ::std::result::Result::Err(#chemin_crate::GenerateError::separator_in_query_param(
+                            #route_variant,
+                            #field_name,
+                            #separator_str,
+                            value,
                         ));
                     }
                 )
             }
-        });
 
-    quote!(#route_variant_pat => {
-        #(#qstring_pairs_adding)*
+            QueryParamKind::Flag => quote_spanned!(field_ident.span()=>
+                if *#field_ident {
+                    #chemin_crate::add_query_flag(__chemin_qstring, #key);
+                }
+            ),
+
+            QueryParamKind::Flatten => {
+                let field_name = field_ident.to_string();
+
+                quote_spanned!(field_ident.span()=>
+                    if let ::std::result::Result::Err(message) =
+                        #chemin_crate::add_flattened_query_params(__chemin_qstring, #field_ident)
+                    {
+                        return ::std::result::Result::Err(#chemin_crate::GenerateError::invalid_query_params(
+                            #route_variant,
+                            #field_name,
+                            message,
+                        ));
+                    }
+                )
+            }
+        }
+    };
+
+    // Query string parameters are only supported for named fields, so the sub-route has to be named to be among them.
+    let sub_route_ident = match route_sub_route_member(route) {
+        Some(Member::Named(ident)) => Some(ident),
+        _ => None,
+    };
+
+    // The query string parameters are added in the order of the fields. With `QueryOrder::Declaration`, those of the sub-route
+    // (which are added when generating its url, after those of this route) are then moved to the position of its field, if some
+    // query string parameters of this route come after it.
+    let mut qstring_pairs_adding = Vec::new();
+    let mut sub_route_position = None;
+
+    for field_ident in route
+        .variant
+        .fields
+        .iter()
+        .filter_map(|field| field.ident.as_ref())
+    {
+        if let Some(query_param) = route
+            .query_params
+            .iter()
+            .find(|query_param| &query_param.field_ident == field_ident)
+        {
+            qstring_pairs_adding.push(query_param_adding(query_param));
+        } else if route.query_rest.as_ref() == Some(field_ident) {
+            qstring_pairs_adding.push(quote_spanned!(field_ident.span()=>
+                #chemin_crate::add_query_rest(__chemin_qstring, #field_ident);
+            ));
+        } else if sub_route_ident.as_ref() == Some(field_ident) {
+            sub_route_position = Some(qstring_pairs_adding.len());
+        }
+    }
+
+    let sub_route_position =
+        sub_route_position.filter(|position| *position < qstring_pairs_adding.len());
+
+    if let Some(position) = sub_route_position {
+        qstring_pairs_adding.insert(
+            position,
+            quote!(let __chemin_sub_route_index = __chemin_qstring.len();),
+        );
+    }
 
+    let url_generation = quote!(
         match __chemin_locale {
             #(#locale_match_arms,)*
-            _ => ::std::option::Option::None,
+            _ => ::std::result::Result::Err(
+                #chemin_crate::GenerateError::missing_locale(#route_variant, __chemin_locale)
+            ),
         }
-    })
+    );
+
+    if sub_route_position.is_some() {
+        quote!(#route_variant_pat => {
+            #(#qstring_pairs_adding)*
+            let __chemin_sub_route_start = __chemin_qstring.len();
+            let url = #url_generation;
+
+            if __chemin_query_order == #chemin_crate::QueryOrder::Declaration {
+                #chemin_crate::move_sub_route_query_params(
+                    __chemin_qstring,
+                    __chemin_sub_route_index,
+                    __chemin_sub_route_start,
+                );
+            }
+
+            url
+        })
+    } else {
+        quote!(#route_variant_pat => {
+            #(#qstring_pairs_adding)*
+            #url_generation
+        })
+    }
 }
 
 fn route_variant_pat(route: &Route) -> TokenStream {
@@ -101,58 +243,256 @@ fn route_variant_pat(route: &Route) -> TokenStream {
     }
 }
 
-fn locale_match_arm(localized_route: &LocalizedRoute, chemin_crate: &TokenStream) -> TokenStream {
+fn locale_match_arm(
+    route: &Route,
+    localized_route: &LocalizedRoute,
+    route_variant: &TokenStream,
+    chemin_crate: &TokenStream,
+) -> TokenStream {
+    let path = &localized_route.path;
+    let forms = path.forms();
+
+    let match_arm_pat = if localized_route.locales.is_empty() {
+        quote!(_)
+    } else {
+        let route_locales = localized_route.locales.iter();
+        quote!(#(::std::option::Option::Some(#route_locales))|*)
+    };
+
+    // The form of the path is chosen according to the params of the optional groups: a group is included if its param is `Some`.
+    let optional_params = path
+        .params()
+        .enumerate()
+        .filter(|(i, _)| path.is_optional_param(*i))
+        .map(|(i, name)| (i, param_ident(name, i, path.span)))
+        .collect::<Vec<_>>();
+
+    if optional_params.is_empty() {
+        let url_generation = url_generation(
+            route,
+            localized_route,
+            &forms[0],
+            route_variant,
+            chemin_crate,
+        );
+        quote!(#match_arm_pat => ::std::result::Result::Ok(#url_generation))
+    } else {
+        let optional_param_idents = optional_params.iter().map(|(_, ident)| ident);
+        let form_match_arms = forms.iter().map(|form| {
+            let pats = optional_params.iter().map(|(i, ident)| {
+                if form.param_indices.contains(i) {
+                    quote!(::std::option::Option::Some(#ident))
+                } else {
+                    quote!(::std::option::Option::None)
+                }
+            });
+            let url_generation =
+                url_generation(route, localized_route, form, route_variant, chemin_crate);
+            quote!((#(#pats,)*) => #url_generation,)
+        });
+
+        quote!(#match_arm_pat => ::std::result::Result::Ok(
+            match (#(#optional_param_idents,)*) {
+                #(#form_match_arms)*
+            }
+        ))
+    }
+}
+
+fn param_ident(name: Option<&String>, i: usize, span: Span) -> Ident {
+    match name {
+        Some(name) => Ident::new(name, span),
+        None => Ident::new(&unnamed_param_name(i), span),
+    }
+}
+
+/// Returns an expression generating the url of a form of the path.
+fn url_generation(
+    route: &Route,
+    localized_route: &LocalizedRoute,
+    form: &PathForm,
+    route_variant: &TokenStream,
+    chemin_crate: &TokenStream,
+) -> TokenStream {
     let mut fmt_str = String::new();
     let mut non_encoded_fmt_args = quote!();
     let mut encoded_fmt_args = quote!();
-    let mut param_i = 0usize;
+    let mut form_param_i = 0usize;
+    // The sub-route or the catch-all param is always the last field.
+    let last_field_i = localized_route.path.params().count();
+
+    let mut push_param = |optional_name: &Option<String>,
+                          optional_constraint: &Option<String>,
+                          in_mixed_segment: bool| {
+        let param_i = form.param_indices[form_param_i];
+        let field_ident = param_ident(optional_name.as_ref(), param_i, localized_route.path.span);
+        let field_name = match optional_name {
+            Some(name) => name.clone(),
+            None => param_i.to_string(),
+        };
+
+        let fmt_arg = match route.param_module(&field_name) {
+            Some(module) => quote!(#module::format(#field_ident)),
+            None => quote!(#field_ident),
+        };
+
+        let mut non_encoded_fmt_arg = fmt_arg.clone();
+        let mut encoded_fmt_arg = quote!(#chemin_crate::encode_param(#fmt_arg));
+
+        if let Some(value) = optional_constraint {
+            // The constraint is checked on the url parameter as it appears in the url.
+            let constraint = constraint(value, chemin_crate);
+            let check = |fmt_arg: TokenStream| {
+                quote!({
+                    let constraint: &#chemin_crate::Constraint = #constraint;
+                    let value = ::std::string::ToString::to_string(&#fmt_arg);
+
+                    if !constraint.matches(&value) {
+                        return ::std::result::Result::Err(#chemin_crate::GenerateError::invalid_param(
+                            #route_variant,
+                            #field_name,
+                            constraint.source,
+                        ));
+                    }
+
+                    value
+                })
+            };
+
+            non_encoded_fmt_arg = check(non_encoded_fmt_arg);
+            encoded_fmt_arg = check(encoded_fmt_arg);
+        } else if in_mixed_segment {
+            // Without a constraint, a url parameter of a mixed segment matches at least one character.
+            let check = |fmt_arg: TokenStream| {
+                quote!({
+                    let value = ::std::string::ToString::to_string(&#fmt_arg);
+
+                    if value.is_empty() {
+                        return ::std::result::Result::Err(#chemin_crate::GenerateError::empty_param(
+                            #route_variant,
+                            #field_name,
+                        ));
+                    }
+
+                    value
+                })
+            };
+
+            non_encoded_fmt_arg = check(non_encoded_fmt_arg);
+            encoded_fmt_arg = check(encoded_fmt_arg);
+        }
+
+        non_encoded_fmt_args = quote!(#non_encoded_fmt_args #non_encoded_fmt_arg,);
+        encoded_fmt_args = quote!(#encoded_fmt_args #encoded_fmt_arg,);
+
+        form_param_i += 1;
+    };
 
-    for path_component in &localized_route.path.components {
+    for path_component in &form.path.components {
         fmt_str.push('/');
 
         match path_component {
             PathComponent::Static(value) => fmt_str.push_str(value),
 
-            PathComponent::Param(optional_name) => {
+            PathComponent::Param(optional_name, optional_constraint) => {
                 fmt_str.push_str("{}");
+                push_param(optional_name, optional_constraint, false);
+            }
 
-                let field_ident = match optional_name {
-                    Some(name) => Ident::new(name, localized_route.path.span),
-                    None => Ident::new(&unnamed_param_name(param_i), localized_route.path.span),
-                };
+            PathComponent::Mixed(parts) => {
+                for part in parts {
+                    match part {
+                        PathComponent::Static(value) => fmt_str.push_str(value),
 
-                non_encoded_fmt_args = quote!(#non_encoded_fmt_args #field_ident,);
-                encoded_fmt_args =
-                    quote!(#encoded_fmt_args #chemin_crate::encode_param(#field_ident),);
+                        PathComponent::Param(optional_name, optional_constraint) => {
+                            fmt_str.push_str("{}");
+                            push_param(optional_name, optional_constraint, true);
+                        }
 
-                param_i += 1;
+                        PathComponent::Optional(_) | PathComponent::Mixed(_) => unreachable!(),
+                    }
+                }
             }
+
+            PathComponent::Optional(_) => unreachable!(),
         }
     }
 
+    if let Some(catch_all) = &localized_route.path.catch_all {
+        let (field_ident, field_name) = match catch_all {
+            CatchAll::Unnamed => (
+                Ident::new(&unnamed_param_name(last_field_i), localized_route.path.span),
+                last_field_i.to_string(),
+            ),
+            CatchAll::Named(name) => (Ident::new(name, localized_route.path.span), name.clone()),
+        };
+
+        fmt_str.push_str("/{}");
+
+        let catch_all_generation = quote!(
+            match #chemin_crate::format_catch_all(#field_ident, __chemin_encode_params) {
+                ::std::option::Option::Some(rest) => rest,
+                ::std::option::Option::None => {
+                    return ::std::result::Result::Err(#chemin_crate::GenerateError::empty_param(
+                        #route_variant,
+                        #field_name,
+                    ))
+                }
+            }
+        );
+        non_encoded_fmt_args = quote!(#non_encoded_fmt_args #catch_all_generation,);
+        encoded_fmt_args = quote!(#encoded_fmt_args #catch_all_generation,);
+    }
+
     if let Some(sub_route) = &localized_route.path.sub_route {
         // We don't do `fmt_str.push('/');` because the url generated by the sub_route is guaranteed to contain a "/" at the beginning.
 
         let sub_route_ident = match sub_route {
             SubRoute::Unnamed => {
-                Ident::new(&unnamed_param_name(param_i), localized_route.path.span)
+                Ident::new(&unnamed_param_name(last_field_i), localized_route.path.span)
             }
             SubRoute::Named(name) => Ident::new(name, localized_route.path.span),
         };
 
         fmt_str.push_str("{}");
 
-        let sub_route_url_generation = quote!(
-            match #chemin_crate::Chemin::generate_url_and_build_qstring(
-                #sub_route_ident,
-                __chemin_locale,
-                __chemin_encode_params,
-                __chemin_qstring,
-            ) {
-                ::std::option::Option::Some(sub_url) => sub_url,
-                ::std::option::Option::None => return ::std::option::Option::None,
+        let sub_route_url_generation = |sub_route_qstring: TokenStream| {
+            quote!(
+                match #chemin_crate::Chemin::generate_url_and_build_qstring(
+                    #sub_route_ident,
+                    __chemin_locale,
+                    __chemin_encode_params,
+                    __chemin_query_order,
+                    #sub_route_qstring,
+                ) {
+                    ::std::result::Result::Ok(sub_url) => sub_url,
+                    ::std::result::Result::Err(error) => {
+                        return ::std::result::Result::Err(error.in_parent_variant(#route_variant))
+                    }
+                }
+            )
+        };
+
+        // With a query prefix, the query string parameters of the sub-route are built separately, to prefix their keys.
+        let sub_route_url_generation = match &route.query_prefix {
+            Some(query_prefix) => {
+                let sub_url_generation =
+                    sub_route_url_generation(quote!(&mut __chemin_sub_route_qstring));
+                quote!({
+                    let mut __chemin_sub_route_qstring =
+                        #chemin_crate::deps::qstring::QString::default();
+                    let sub_url = #sub_url_generation;
+                    #chemin_crate::add_query_prefix(
+                        __chemin_qstring,
+                        __chemin_sub_route_qstring,
+                        #query_prefix,
+                    );
+                    sub_url
+                })
             }
-        );
+
+            None => sub_route_url_generation(quote!(__chemin_qstring)),
+        };
         non_encoded_fmt_args = quote!(#non_encoded_fmt_args #sub_route_url_generation);
         encoded_fmt_args = quote!(#encoded_fmt_args #sub_route_url_generation);
     }
@@ -161,18 +501,9 @@ fn locale_match_arm(localized_route: &LocalizedRoute, chemin_crate: &TokenStream
         fmt_str.push('/');
     }
 
-    let match_arm_pat = if localized_route.locales.is_empty() {
-        quote!(_)
+    quote!(if __chemin_encode_params {
+        format!(#fmt_str, #encoded_fmt_args)
     } else {
-        let route_locales = localized_route.locales.iter();
-        quote!(#(::std::option::Option::Some(#route_locales))|*)
-    };
-
-    quote!(#match_arm_pat => ::std::option::Option::Some(
-        if __chemin_encode_params {
-            format!(#fmt_str, #encoded_fmt_args)
-        } else {
-            format!(#fmt_str, #non_encoded_fmt_args)
-        }
-    ))
+        format!(#fmt_str, #non_encoded_fmt_args)
+    })
 }
@@ -1,18 +1,31 @@
 use super::router::*;
-use super::unnamed_param_name;
+use super::{constraint, option_str, route_locales, route_variant, trailing_slash};
 use proc_macro2::{Span, TokenStream};
 use quote::{quote, quote_spanned};
+use std::cmp::Reverse;
 use std::iter;
 use syn::{Fields, Ident};
 
-static UNNAMED_SUB_ROUTE_NAME: &str = "sub_route";
-
-pub fn parsing_method(routes: &[Route], chemin_crate: &TokenStream) -> TokenStream {
-    let lazy_type = quote!(#chemin_crate::deps::once_cell::sync::Lazy);
-    let router_type = quote!(#chemin_crate::deps::route_recognizer::Router);
-
-    let router_entries = router_entries(routes);
-    let route_handlers = route_handlers(routes, chemin_crate);
+pub fn parsing_method(
+    enum_ident: &Ident,
+    routes: &[Route],
+    options: &RouterOptions,
+    chemin_crate: &TokenStream,
+) -> TokenStream {
+    let localized_routes = localized_routes_by_priority(routes);
+    let trailing_slash = trailing_slash(options.trailing_slash, chemin_crate);
+    let patterns = localized_routes
+        .iter()
+        .map(|(_, _, form)| pattern(&form.path, options, chemin_crate));
+    let route_handlers =
+        localized_routes
+            .iter()
+            .enumerate()
+            .map(|(i, (route, localized_route, form))| {
+                let route_handler =
+                    route_handler(enum_ident, route, localized_route, form, chemin_crate);
+                quote!(#i => #route_handler,)
+            });
 
     quote!(
         fn parse_with_accepted_locales(
@@ -20,117 +33,117 @@ pub fn parsing_method(routes: &[Route], chemin_crate: &TokenStream) -> TokenStre
             accepted_locales: &#chemin_crate::AcceptedLocales,
             decode_params: ::std::primitive::bool,
             qstring: &#chemin_crate::deps::qstring::QString,
-        ) -> ::std::option::Option<(Self, ::std::vec::Vec<#chemin_crate::Locale>)> {
-            static ROUTER: #lazy_type<#router_type<u32>> = #lazy_type::new(|| {
-                let mut router = #router_type::new();
-                #router_entries
-                router
-            });
+            non_canonical: &::std::cell::Cell<::std::primitive::bool>,
+        ) -> ::std::result::Result<(Self, ::std::vec::Vec<#chemin_crate::Locale>), #chemin_crate::ParseError> {
+            static PATTERNS: &[#chemin_crate::Pattern] = &[#(#patterns),*];
+
+            #chemin_crate::parse_with_trailing_slash(path, #trailing_slash, |path| {
+                let mut first_error = ::std::option::Option::None;
+
+                // Every route matching the path is tried, until one of them is successfully parsed.
+                for (i, pattern) in ::std::iter::Iterator::enumerate(PATTERNS.iter()) {
+                    let match_ = match pattern.matches(path) {
+                        ::std::option::Option::Some(match_) => match_,
+                        ::std::option::Option::None => continue,
+                    };
+                    let route_non_canonical = ::std::cell::Cell::new(match_.case_mismatch);
+
+                    let result: ::std::result::Result<(Self, ::std::vec::Vec<#chemin_crate::Locale>), #chemin_crate::ParseError> =
+                        match i {
+                            #(#route_handlers)*
+                            _ => ::std::unreachable!(),
+                        };
+
+                    match result {
+                        ::std::result::Result::Ok(value) => {
+                            if route_non_canonical.get() {
+                                non_canonical.set(true);
+                            }
 
-            match ROUTER.recognize(path) {
-                ::std::result::Result::Ok(match_) => {
-                    let params = match_.params();
-                    match *match_.handler() {
-                        #route_handlers
-                        _ => ::std::option::Option::None
+                            return ::std::result::Result::Ok(value);
+                        }
+                        ::std::result::Result::Err(#chemin_crate::ParseError::NotFound) => (),
+                        ::std::result::Result::Err(error) => {
+                            first_error.get_or_insert(error);
+                        }
                     }
-                },
+                }
 
-                ::std::result::Result::Err(_) => ::std::option::Option::None,
-            }
+                ::std::result::Result::Err(first_error.unwrap_or(#chemin_crate::ParseError::NotFound))
+            })
         }
     )
 }
 
-fn router_entries(routes: &[Route]) -> TokenStream {
-    let mut router_entries = quote!();
-    let mut i = 0u32;
-
-    for route in routes {
-        for localized_route in &route.localized_routes {
-            let route_recognizer_path = path_to_route_recognizer_path(&localized_route.path);
-            router_entries = quote!(
-                #router_entries
-                router.add(#route_recognizer_path, #i);
-            );
-            i += 1;
-        }
-    }
-
-    router_entries
+/// Returns the forms of the localized routes by decreasing priority. Localized routes with the same priority are kept in the order of
+/// declaration, and the forms of a localized route are sorted by decreasing length.
+fn localized_routes_by_priority(routes: &[Route]) -> Vec<(&Route, &LocalizedRoute, PathForm)> {
+    let mut localized_routes = routes
+        .iter()
+        .flat_map(|route| {
+            route
+                .localized_routes
+                .iter()
+                .flat_map(move |localized_route| {
+                    localized_route
+                        .path
+                        .forms()
+                        .into_iter()
+                        .map(move |form| (route, localized_route, form))
+                })
+        })
+        .collect::<Vec<_>>();
+    localized_routes.sort_by_key(|(route, _, _)| Reverse(route.priority));
+    localized_routes
 }
 
-fn path_to_route_recognizer_path(path: &Path) -> String {
-    let mut route_recognizer_path = String::new();
-    let mut param_i = 0usize;
-
-    for component in &path.components {
-        match component {
-            PathComponent::Static(value) => {
-                route_recognizer_path.push('/');
-                route_recognizer_path.push_str(value);
-            }
-
-            PathComponent::Param(name) => {
-                route_recognizer_path.push_str("/:");
-                match name {
-                    Some(name) => route_recognizer_path.push_str(name),
-                    None => route_recognizer_path.push_str(&unnamed_param_name(param_i)),
-                }
-                param_i += 1;
-            }
+fn pattern(path: &Path, options: &RouterOptions, chemin_crate: &TokenStream) -> TokenStream {
+    let components = path.components.iter().map(|component| match component {
+        PathComponent::Static(value) => quote!(#chemin_crate::PatternComponent::Static(#value)),
+        PathComponent::Param(_, None) => {
+            quote!(#chemin_crate::PatternComponent::Param(::std::option::Option::None))
         }
-    }
-
-    if let Some(sub_route) = &path.sub_route {
-        route_recognizer_path.push_str("/*");
-
-        match sub_route {
-            SubRoute::Unnamed => route_recognizer_path.push_str(UNNAMED_SUB_ROUTE_NAME),
-            SubRoute::Named(name) => route_recognizer_path.push_str(name),
+        PathComponent::Param(_, Some(value)) => {
+            let constraint = constraint(value, chemin_crate);
+            quote!(#chemin_crate::PatternComponent::Param(::std::option::Option::Some(#constraint)))
         }
-    }
-
-    if path.trailing_slash {
-        route_recognizer_path.push('/');
-    }
-
-    route_recognizer_path
-}
-
-fn route_handlers(routes: &[Route], chemin_crate: &TokenStream) -> TokenStream {
-    let mut route_handlers = quote!();
-    let mut i = 0u32;
-
-    for route in routes {
-        for localized_route in &route.localized_routes {
-            let route_handler = route_handler(route, localized_route, chemin_crate);
-            route_handlers = quote!(#route_handlers #i => #route_handler,);
-            i += 1;
+        PathComponent::Mixed(parts) => {
+            let constraint = constraint(&PathComponent::mixed_segment_regex(parts), chemin_crate);
+            quote!(#chemin_crate::PatternComponent::Mixed(#constraint))
         }
-    }
-
-    route_handlers
+        PathComponent::Optional(_) => unreachable!(),
+    });
+    let sub_route = path.sub_route.is_some();
+    let catch_all = path.catch_all.is_some();
+    let trailing_slash = path.trailing_slash;
+    let case_insensitive = options.case_insensitive;
+
+    quote!(#chemin_crate::Pattern {
+        components: &[#(#components),*],
+        sub_route: #sub_route,
+        catch_all: #catch_all,
+        trailing_slash: #trailing_slash,
+        case_insensitive: #case_insensitive,
+    })
 }
 
 fn route_handler(
+    enum_ident: &Ident,
     route: &Route,
     localized_route: &LocalizedRoute,
+    form: &PathForm,
     chemin_crate: &TokenStream,
 ) -> TokenStream {
-    let route_locales = if localized_route.locales.is_empty() {
-        quote!(#chemin_crate::RouteLocales::Any)
-    } else {
-        let route_locales = localized_route.locales.iter();
-        quote!(#chemin_crate::RouteLocales::Some(&[#(#route_locales),*]))
-    };
+    let route_locales = route_locales(localized_route, chemin_crate);
+    let route_variant = route_variant(enum_ident, route, chemin_crate);
 
     let sub_route_parsing = match &localized_route.path.sub_route {
-        Some(sub_route) => sub_route_parsing(localized_route, sub_route, chemin_crate),
+        Some(_) => sub_route_parsing(route, localized_route, &route_variant, chemin_crate),
         None => quote!(),
     };
 
-    let route_variant_building = route_variant_building(route, localized_route, chemin_crate);
+    let route_variant_building =
+        route_variant_building(route, localized_route, form, &route_variant, chemin_crate);
 
     let resulting_locales = if localized_route.path.sub_route.is_some() {
         quote!(sub_route_resulting_locales)
@@ -138,35 +151,46 @@ fn route_handler(
         quote!(accepted_locales.resulting_locales(&ROUTE_LOCALES))
     };
 
-    quote!({
+    // The handler is a closure, so that its early returns don't return from the parsing method.
+    quote!((|| {
         static ROUTE_LOCALES: #chemin_crate::RouteLocales = #route_locales;
 
         if accepted_locales.accept(&ROUTE_LOCALES) {
             #sub_route_parsing
-            ::std::option::Option::Some((#route_variant_building, #resulting_locales))
+            ::std::result::Result::Ok((#route_variant_building, #resulting_locales))
         } else {
-            ::std::option::Option::None
+            ::std::result::Result::Err(#chemin_crate::ParseError::NotFound)
         }
-    })
+    })())
 }
 
 fn sub_route_parsing(
+    route: &Route,
     localized_route: &LocalizedRoute,
-    sub_route: &SubRoute,
+    route_variant: &TokenStream,
     chemin_crate: &TokenStream,
 ) -> TokenStream {
-    let sub_route_param_name = match sub_route {
-        SubRoute::Unnamed => UNNAMED_SUB_ROUTE_NAME,
-        SubRoute::Named(name) => name,
+    // With a query prefix, the sub-route only sees the query string parameters whose keys start with it, without it.
+    let sub_route_qstring = match &route.query_prefix {
+        Some(query_prefix) => quote!(&#chemin_crate::strip_query_prefix(qstring, #query_prefix)),
+        None => quote!(qstring),
     };
 
     quote_spanned!(localized_route.path.span=>
-        let sub_route_path = params.find(#sub_route_param_name).unwrap();
+        let sub_route_path = match_.sub_route_path.unwrap();
         let sub_route_accepted_locales = accepted_locales.accepted_locales_for_sub_route(&ROUTE_LOCALES);
         let (sub_route, sub_route_resulting_locales) =
-            match #chemin_crate::Chemin::parse_with_accepted_locales(sub_route_path, &sub_route_accepted_locales, decode_params, qstring) {
-                ::std::option::Option::Some(value) => value,
-                ::std::option::Option::None => return ::std::option::Option::None,
+            match #chemin_crate::Chemin::parse_with_accepted_locales(
+                sub_route_path,
+                &sub_route_accepted_locales,
+                decode_params,
+                #sub_route_qstring,
+                &route_non_canonical,
+            ) {
+                ::std::result::Result::Ok(value) => value,
+                ::std::result::Result::Err(error) => {
+                    return ::std::result::Result::Err(error.in_parent_variant(#route_variant))
+                }
             };
     )
 }
@@ -174,25 +198,93 @@ fn sub_route_parsing(
 fn route_variant_building(
     route: &Route,
     localized_route: &LocalizedRoute,
+    form: &PathForm,
+    route_variant: &TokenStream,
     chemin_crate: &TokenStream,
 ) -> TokenStream {
-    fn parsing_code(str_exp: TokenStream, span: Span, chemin_crate: &TokenStream) -> TokenStream {
+    let field_error = |field_name: &str, kind: TokenStream| {
+        quote!(
+            return ::std::result::Result::Err(#chemin_crate::ParseError::invalid_field(
+                #route_variant,
+                #field_name,
+                #chemin_crate::FieldErrorKind::#kind,
+            ))
+        )
+    };
+
+    let error_message = quote!({
+        use #chemin_crate::{DisplayErrorMessage as _, GenericErrorMessage as _};
+        (&#chemin_crate::ErrorMessage(&error)).error_message()
+    });
+
+    let parse_fn = |field_name: &str| match route.param_module(field_name) {
+        Some(module) => quote!(#module::parse),
+        None => quote!(::std::primitive::str::parse),
+    };
+
+    let parsing_code = |str_exp: TokenStream, field_name: &str, span: Span| {
+        let invalid_encoding_error = field_error(field_name, quote!(InvalidEncoding));
+        let invalid_param_error = field_error(field_name, quote!(InvalidParam(#error_message)));
+        let parse_fn = parse_fn(field_name);
+
         quote_spanned!(span=> {
             let value = if decode_params {
                 match #chemin_crate::decode_param(#str_exp) {
-                    Some(value) => value,
-                    None => return None,
+                    ::std::option::Option::Some(value) => value,
+                    ::std::option::Option::None => #invalid_encoding_error,
                 }
             } else {
                 ::std::borrow::Cow::Borrowed(#str_exp)
             };
 
-            match ::std::primitive::str::parse(&value) {
+            match #parse_fn(&value) {
                 ::std::result::Result::Ok(value) => value,
-                ::std::result::Result::Err(_) => return ::std::option::Option::None,
+                ::std::result::Result::Err(error) => #invalid_param_error,
             }
         })
-    }
+    };
+
+    // The params inside the omitted optional groups are `None`, and the ones inside the included optional groups are wrapped in
+    // `Some`.
+    let param_parsing_code = |i: usize, field_name: &str| match form
+        .param_indices
+        .iter()
+        .position(|param_index| *param_index == i)
+    {
+        Some(match_index) => {
+            let parsing_code = parsing_code(
+                quote!(match_.params[#match_index]),
+                field_name,
+                localized_route.path.span,
+            );
+
+            if localized_route.path.is_optional_param(i) {
+                quote!(::std::option::Option::Some(#parsing_code))
+            } else {
+                parsing_code
+            }
+        }
+
+        None => quote!(::std::option::Option::None),
+    };
+
+    // The catch-all param is always the last url parameter.
+    let catch_all_index = form.param_indices.len();
+    let catch_all_field_index = localized_route.path.params().count();
+    let catch_all_parsing_code = |field_name: &str| {
+        quote_spanned!(localized_route.path.span=>
+            match #chemin_crate::parse_catch_all(match_.params[#catch_all_index], decode_params) {
+                ::std::result::Result::Ok(value) => value,
+                ::std::result::Result::Err(kind) => {
+                    return ::std::result::Result::Err(#chemin_crate::ParseError::invalid_field(
+                        #route_variant,
+                        #field_name,
+                        kind,
+                    ))
+                }
+            }
+        )
+    };
 
     match route.variant.fields {
         Fields::Named(_) => {
@@ -200,15 +292,21 @@ fn route_variant_building(
                 .path
                 .params()
                 .map(|param| param.unwrap())
-                .map(|param| {
+                .enumerate()
+                .map(|(i, param)| {
                     let field_ident = Ident::new(param, localized_route.path.span);
-                    let parsing_code = parsing_code(
-                        quote!(params.find(#param).unwrap()),
-                        localized_route.path.span,
-                        chemin_crate,
-                    );
+                    let parsing_code = param_parsing_code(i, param);
                     quote!(#field_ident: #parsing_code)
                 })
+                .chain(match &localized_route.path.catch_all {
+                    Some(CatchAll::Named(name)) => {
+                        let field_ident = Ident::new(name, localized_route.path.span);
+                        let parsing_code = catch_all_parsing_code(name);
+                        Some(quote!(#field_ident: #parsing_code))
+                    }
+                    Some(CatchAll::Unnamed) => unreachable!(),
+                    None => None,
+                })
                 .chain(match &localized_route.path.sub_route {
                     Some(sub_route) => match sub_route {
                         SubRoute::Unnamed => unreachable!(),
@@ -222,37 +320,92 @@ fn route_variant_building(
                     None => Box::new(iter::empty()) as Box<dyn Iterator<Item = _>>,
                 })
                 .chain(route.query_params.iter().map(|query_param| {
-                    match query_param {
-                        QueryParam::Mandatory(field_ident) => quote_spanned!(field_ident.span()=>
-                            #field_ident: match qstring.get(::std::stringify!(#field_ident)) {
-                                ::std::option::Option::Some(value) => match ::std::primitive::str::parse(value) {
-                                    ::std::result::Result::Ok(value) => value,
-                                    ::std::result::Result::Err(_) => return ::std::option::Option::None,
-                                },
-                                ::std::option::Option::None => return ::std::option::Option::None,
-                            }
-                        ),
+                    let field_ident = &query_param.field_ident;
+                    let field_name = field_ident.to_string();
+                    let keys = query_param.keys().collect::<Vec<_>>();
+                    let invalid_query_param_error = field_error(
+                        &field_name,
+                        quote!(InvalidQueryParam(#error_message)),
+                    );
+                    let parse_fn = parse_fn(&field_name);
+
+                    match &query_param.kind {
+                        QueryParamKind::Mandatory => {
+                            let missing_query_param_error =
+                                field_error(&field_name, quote!(MissingQueryParam));
+
+                            quote_spanned!(field_ident.span()=>
+                                #field_ident: match #chemin_crate::query_param_value(qstring, &[#(#keys),*]) {
+                                    ::std::option::Option::Some(value) => match #parse_fn(value) {
+                                        ::std::result::Result::Ok(value) => value,
+                                        ::std::result::Result::Err(error) => #invalid_query_param_error,
+                                    },
+                                    ::std::option::Option::None => #missing_query_param_error,
+                                }
+                            )
+                        }
 
-                        QueryParam::Optional(field_ident) => quote_spanned!(field_ident.span()=>
-                            #field_ident: match qstring.get(::std::stringify!(#field_ident)) {
-                                ::std::option::Option::Some(value) => match ::std::primitive::str::parse(value) {
+                        QueryParamKind::Optional => quote_spanned!(field_ident.span()=>
+                            #field_ident: match #chemin_crate::query_param_value(qstring, &[#(#keys),*]) {
+                                ::std::option::Option::Some(value) => match #parse_fn(value) {
                                     ::std::result::Result::Ok(value) => ::std::option::Option::Some(value),
-                                    ::std::result::Result::Err(_) => return ::std::option::Option::None,
+                                    ::std::result::Result::Err(error) => #invalid_query_param_error,
                                 },
                                 ::std::option::Option::None => ::std::option::Option::None,
                             }
                         ),
 
-                        QueryParam::WithDefaultValue(field_ident, default_value) => quote_spanned!(field_ident.span()=>
-                            #field_ident: match qstring.get(::std::stringify!(#field_ident)) {
-                                ::std::option::Option::Some(value) => match ::std::primitive::str::parse(value) {
+                        QueryParamKind::WithDefaultValue(default_value) => quote_spanned!(field_ident.span()=>
+                            #field_ident: match #chemin_crate::query_param_value(qstring, &[#(#keys),*]) {
+                                ::std::option::Option::Some(value) => match #parse_fn(value) {
                                     ::std::result::Result::Ok(value) => value,
-                                    ::std::result::Result::Err(_) => return ::std::option::Option::None,
+                                    ::std::result::Result::Err(error) => #invalid_query_param_error,
                                 },
                                 ::std::option::Option::None => #default_value,
                             }
                         ),
+
+                        QueryParamKind::Flag => quote_spanned!(field_ident.span()=>
+                            #field_ident: #chemin_crate::parse_query_flag(
+                                #chemin_crate::query_param_value(qstring, &[#(#keys),*]),
+                            )
+                        ),
+
+                        QueryParamKind::Flatten => quote_spanned!(field_ident.span()=>
+                            #field_ident: {
+                                #chemin_crate::require_serde_feature!();
+
+                                match #chemin_crate::parse_flattened_query_params(qstring) {
+                                    ::std::result::Result::Ok(value) => value,
+                                    ::std::result::Result::Err(error) => #invalid_query_param_error,
+                                }
+                            }
+                        ),
+
+                        QueryParamKind::Multiple(separator) => {
+                            let separator = option_str(separator.as_deref());
+
+                            quote_spanned!(field_ident.span()=>
+                                #field_ident: match ::std::iter::Iterator::collect::<::std::result::Result<_, _>>(
+                                    ::std::iter::Iterator::map(
+                                        ::std::iter::IntoIterator::into_iter(#chemin_crate::query_param_values(
+                                            qstring,
+                                            &[#(#keys),*],
+                                            #separator,
+                                        )),
+                                        #parse_fn,
+                                    ),
+                                ) {
+                                    ::std::result::Result::Ok(value) => value,
+                                    ::std::result::Result::Err(error) => #invalid_query_param_error,
+                                }
+                            )
+                        }
                     }
+                }))
+                // The query rest is filled once the whole route tree is parsed (see `Chemin::set_query_rest`).
+                .chain(route.query_rest.iter().map(|field_ident| {
+                    quote_spanned!(field_ident.span()=> #field_ident: ::std::default::Default::default())
                 }));
             let variant_ident = &route.variant.ident;
             quote_spanned!(localized_route.path.span=> Self::#variant_ident { #(#fields),* })
@@ -263,13 +416,13 @@ fn route_variant_building(
                 .path
                 .params()
                 .enumerate()
-                .map(|(i, _)| {
-                    let param_name = unnamed_param_name(i);
-                    parsing_code(
-                        quote!(params.find(#param_name).unwrap()),
-                        localized_route.path.span,
-                        chemin_crate,
-                    )
+                .map(|(i, _)| param_parsing_code(i, &i.to_string()))
+                .chain(match &localized_route.path.catch_all {
+                    Some(CatchAll::Unnamed) => {
+                        Some(catch_all_parsing_code(&catch_all_field_index.to_string()))
+                    }
+                    Some(CatchAll::Named(_)) => unreachable!(),
+                    None => None,
                 })
                 .chain(match &localized_route.path.sub_route {
                     Some(sub_route) => match sub_route {
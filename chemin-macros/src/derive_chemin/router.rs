@@ -5,27 +5,275 @@ use quote::ToTokens;
 use crate::helpers;
 use proc_macro2::Span;
 use proc_macro2::TokenStream;
+use std::collections::{HashMap, HashSet};
+use std::iter;
 use syn::parse::{Parse, ParseBuffer};
+use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use syn::{parenthesized, Error, Expr, Fields, Ident, ItemEnum, Token, Variant};
+use syn::{
+    parenthesized, Attribute, Error, Expr, Fields, Ident, ItemEnum, LitInt, LitStr, Token, Variant,
+};
 
 pub struct Router {
     pub item_enum: ItemEnum,
+    pub options: RouterOptions,
     pub routes: Vec<Route>,
 }
 
 impl Router {
     pub fn parse(item: TokenStream) -> syn::Result<Self> {
         let item_enum: ItemEnum = syn::parse2(item)?;
+        let options = RouterOptions::from_attrs(&item_enum.attrs)?;
+        let routes = item_enum
+            .variants
+            .iter()
+            .map(Route::from_variant)
+            .collect::<syn::Result<Vec<Route>>>()?;
+        check_ambiguities(&routes, &options)?;
         Ok(Self {
-            routes: item_enum
-                .variants
-                .iter()
-                .map(Route::from_variant)
-                .collect::<syn::Result<Vec<Route>>>()?,
             item_enum,
+            options,
+            routes,
+        })
+    }
+}
+
+/// The options of the enum, defined with `#[chemin(...)]`.
+#[derive(Default, PartialEq, Eq, Debug)]
+pub struct RouterOptions {
+    pub trailing_slash: TrailingSlash,
+    /// Whether the static segments are matched case-insensitively, with `#[chemin(case_insensitive)]`.
+    pub case_insensitive: bool,
+    pub unknown_query: UnknownQuery,
+    pub query_order: QueryOrder,
+}
+
+impl RouterOptions {
+    fn from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut options = Self::default();
+        let mut trailing_slash_is_defined = false;
+        let mut unknown_query_is_defined = false;
+        let mut query_order_is_defined = false;
+
+        for attr in attrs.iter().filter(|attr| attr.path.is_ident("chemin")) {
+            let CheminAttribute(router_options) = syn::parse2(attr.tokens.clone())?;
+
+            for router_option in router_options {
+                match router_option {
+                    RouterOption::TrailingSlash(span, trailing_slash) => {
+                        if trailing_slash_is_defined {
+                            return Err(Error::new(
+                                span,
+                                "The trailing slash policy can only be defined once",
+                            ));
+                        }
+
+                        options.trailing_slash = trailing_slash;
+                        trailing_slash_is_defined = true;
+                    }
+
+                    RouterOption::CaseInsensitive(span) => {
+                        if options.case_insensitive {
+                            return Err(Error::new(
+                                span,
+                                "`case_insensitive` can only be used once",
+                            ));
+                        }
+
+                        options.case_insensitive = true;
+                    }
+
+                    RouterOption::UnknownQuery(span, unknown_query) => {
+                        if unknown_query_is_defined {
+                            return Err(Error::new(
+                                span,
+                                "The unknown query policy can only be defined once",
+                            ));
+                        }
+
+                        options.unknown_query = unknown_query;
+                        unknown_query_is_defined = true;
+                    }
+
+                    RouterOption::QueryOrder(span, query_order) => {
+                        if query_order_is_defined {
+                            return Err(Error::new(
+                                span,
+                                "The query order can only be defined once",
+                            ));
+                        }
+
+                        options.query_order = query_order;
+                        query_order_is_defined = true;
+                    }
+                }
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+/// How the trailing slash of a url is handled when parsing it. See `chemin::TrailingSlash`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum TrailingSlash {
+    #[default]
+    Strict,
+    Ignore,
+    Redirect,
+}
+
+/// How the query string parameters which aren't claimed by any field are handled when parsing a url. See `chemin::UnknownQuery`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum UnknownQuery {
+    #[default]
+    Ignore,
+    Reject,
+    Capture,
+}
+
+/// The order of the query string parameters in the generated urls. See `chemin::QueryOrder`.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum QueryOrder {
+    #[default]
+    Tree,
+    Declaration,
+    Alphabetical,
+}
+
+/// `#[chemin(option, option = "...", ...)]`, on the enum.
+struct CheminAttribute(Punctuated<RouterOption, Token![,]>);
+
+impl Parse for CheminAttribute {
+    fn parse(input: &ParseBuffer) -> syn::Result<Self> {
+        let content;
+        parenthesized!(content in input);
+        helpers::parse_eos(input)?;
+        Ok(Self(Punctuated::parse_terminated(&content)?))
+    }
+}
+
+enum RouterOption {
+    TrailingSlash(Span, TrailingSlash),
+    CaseInsensitive(Span),
+    UnknownQuery(Span, UnknownQuery),
+    QueryOrder(Span, QueryOrder),
+}
+
+impl Parse for RouterOption {
+    fn parse(input: &ParseBuffer) -> syn::Result<Self> {
+        let ident: Ident = input.parse()?;
+
+        if ident == "trailing_slash" {
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+            let trailing_slash = match &value.value()[..] {
+                "strict" => TrailingSlash::Strict,
+                "ignore" => TrailingSlash::Ignore,
+                "redirect" => TrailingSlash::Redirect,
+                _ => {
+                    return Err(Error::new(
+                        value.span(),
+                        "Expected \"strict\", \"ignore\" or \"redirect\"",
+                    ))
+                }
+            };
+            Ok(Self::TrailingSlash(ident.span(), trailing_slash))
+        } else if ident == "case_insensitive" {
+            Ok(Self::CaseInsensitive(ident.span()))
+        } else if ident == "unknown_query" {
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+            let unknown_query = match &value.value()[..] {
+                "ignore" => UnknownQuery::Ignore,
+                "reject" => UnknownQuery::Reject,
+                "capture" => UnknownQuery::Capture,
+                _ => {
+                    return Err(Error::new(
+                        value.span(),
+                        "Expected \"ignore\", \"reject\" or \"capture\"",
+                    ))
+                }
+            };
+            Ok(Self::UnknownQuery(ident.span(), unknown_query))
+        } else if ident == "query_order" {
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+            let query_order = match &value.value()[..] {
+                "tree" => QueryOrder::Tree,
+                "declaration" => QueryOrder::Declaration,
+                "alphabetical" => QueryOrder::Alphabetical,
+                _ => {
+                    return Err(Error::new(
+                        value.span(),
+                        "Expected \"tree\", \"declaration\" or \"alphabetical\"",
+                    ))
+                }
+            };
+            Ok(Self::QueryOrder(ident.span(), query_order))
+        } else {
+            Err(Error::new(
+                ident.span(),
+                "Expected `trailing_slash = \"...\"`, `case_insensitive`, `unknown_query = \"...\"` or `query_order = \"...\"`",
+            ))
+        }
+    }
+}
+
+/// Returns an error if some url could be matched by two different routes with the same priority, for a common locale.
+fn check_ambiguities(routes: &[Route], options: &RouterOptions) -> syn::Result<()> {
+    let localized_routes = routes
+        .iter()
+        .flat_map(|route| {
+            route
+                .localized_routes
+                .iter()
+                .map(move |localized_route| (route.priority, localized_route))
         })
+        .collect::<Vec<_>>();
+    let mut result: syn::Result<()> = Ok(());
+    let mut add_error = |error| match &mut result {
+        Ok(()) => result = Err(error),
+        Err(errors) => errors.combine(error),
+    };
+
+    for (_, localized_route) in &localized_routes {
+        if let Some((a, b)) = localized_route
+            .path
+            .ambiguous_forms(options.case_insensitive)?
+        {
+            add_error(Error::new(
+                localized_route.path.span,
+                format!(
+                    "This route is ambiguous: its optional groups can produce the paths \"{}\" and \"{}\", which can match the same \
+                    urls (add static segments to distinguish them)",
+                    a.path, b.path,
+                ),
+            ));
+        }
     }
+
+    for (i, (a_priority, a)) in localized_routes.iter().enumerate() {
+        for (b_priority, b) in &localized_routes[i + 1..] {
+            if a_priority == b_priority && a.overlaps(b, options.case_insensitive)? {
+                let mut error = Error::new(
+                    b.path.span,
+                    format!(
+                        "This route is ambiguous: some urls can be matched both by \"{}\" and by \"{}\", for the same locales \
+                        (use `#[route(priority = ...)]` to choose which one is tried first)",
+                        a.path, b.path,
+                    ),
+                );
+                error.combine(Error::new(
+                    a.path.span,
+                    format!("\"{}\" is defined here", a.path),
+                ));
+                add_error(error);
+            }
+        }
+    }
+
+    result
 }
 
 #[derive(PartialEq, Eq, Debug)]
@@ -33,6 +281,14 @@ pub struct Route {
     pub variant: Variant,
     pub localized_routes: Vec<LocalizedRoute>,
     pub query_params: Vec<QueryParam>,
+    /// The field collecting the query string parameters which aren't claimed by any field, defined with `#[query_rest]`.
+    pub query_rest: Option<Ident>,
+    /// The prefix of the query string parameter keys of the sub-route, defined with `#[route("...", query_prefix = "...")]`.
+    pub query_prefix: Option<String>,
+    /// The modules defined with `#[param(with = "...")]`, by field name (for unnamed fields, the index of the field).
+    pub param_modules: HashMap<String, syn::Path>,
+    /// Routes with a higher priority are tried first when parsing a url.
+    pub priority: i32,
 }
 
 impl Route {
@@ -41,11 +297,58 @@ impl Route {
             variant: variant.clone(),
             localized_routes: Vec::new(),
             query_params: Vec::new(),
+            query_rest: None,
+            query_prefix: None,
+            param_modules: HashMap::new(),
+            priority: 0,
         };
+        let mut priority_is_defined = false;
 
         for attr in &variant.attrs {
             if attr.path.is_ident("route") {
-                let new_localized_route: LocalizedRoute = syn::parse2(attr.tokens.clone())?;
+                let new_localized_route = match syn::parse2(attr.tokens.clone())? {
+                    RouteAttribute::LocalizedRoute(localized_route, query_prefix) => {
+                        if let Some(query_prefix) = query_prefix {
+                            if localized_route.path.sub_route.is_none() {
+                                return Err(Error::new(
+                                    query_prefix.span(),
+                                    "`query_prefix` can only be used on a route with a sub-route",
+                                ));
+                            }
+
+                            if route.query_prefix.is_some() {
+                                return Err(Error::new(
+                                    query_prefix.span(),
+                                    "The query prefix of a route can only be defined once",
+                                ));
+                            }
+
+                            if query_prefix.value().is_empty() {
+                                return Err(Error::new(
+                                    query_prefix.span(),
+                                    "The query prefix can't be empty",
+                                ));
+                            }
+
+                            route.query_prefix = Some(query_prefix.value());
+                        }
+
+                        localized_route
+                    }
+
+                    RouteAttribute::Priority(priority) => {
+                        if priority_is_defined {
+                            return Err(Error::new(
+                                attr.tokens.span(),
+                                "The priority of a route can only be defined once",
+                            ));
+                        }
+
+                        route.priority = priority;
+                        priority_is_defined = true;
+                        continue;
+                    }
+                };
                 validate_localized_route(&new_localized_route, variant, attr.tokens.span())?;
 
                 if new_localized_route
@@ -64,9 +367,9 @@ impl Route {
                     .iter_mut()
                     .find(|localized_route| localized_route.path == new_localized_route.path)
                 {
-                    Some(localized_route) => localized_route
-                        .locales
-                        .extend(new_localized_route.locales.into_iter()),
+                    Some(localized_route) => {
+                        localized_route.locales.extend(new_localized_route.locales)
+                    }
 
                     None => route.localized_routes.push(new_localized_route),
                 }
@@ -82,7 +385,7 @@ impl Route {
                 match &field.ident {
                     Some(field_ident) => {
                         let mut token_stream_to_parse = field_ident.into_token_stream();
-                        token_stream_to_parse.extend(attr.tokens.clone().into_iter());
+                        token_stream_to_parse.extend(attr.tokens.clone());
                         route.query_params.push(syn::parse2(token_stream_to_parse)?);
                     }
 
@@ -94,6 +397,48 @@ impl Route {
                     }
                 }
             }
+
+            if let Some(attr) = field
+                .attrs
+                .iter()
+                .find(|attr| attr.path.is_ident("query_rest"))
+            {
+                if !attr.tokens.is_empty() {
+                    return Err(Error::new(
+                        attr.tokens.span(),
+                        "`#[query_rest]` doesn't take any argument",
+                    ));
+                }
+
+                if field
+                    .attrs
+                    .iter()
+                    .any(|attr| attr.path.is_ident("query_param"))
+                {
+                    return Err(Error::new(
+                        attr.path.span(),
+                        "A field can't be both a query param and a query rest",
+                    ));
+                }
+
+                if route.query_rest.is_some() {
+                    return Err(Error::new(
+                        attr.path.span(),
+                        "Only one field of a variant can be a query rest",
+                    ));
+                }
+
+                match &field.ident {
+                    Some(field_ident) => route.query_rest = Some(field_ident.clone()),
+
+                    None => {
+                        return Err(Error::new(
+                            attr.path.span(),
+                            "Only named fields can be query rests",
+                        ))
+                    }
+                }
+            }
         }
 
         if route.localized_routes.is_empty() {
@@ -103,6 +448,84 @@ impl Route {
             ));
         }
 
+        let mut query_param_keys = HashSet::new();
+
+        for query_param in &route.query_params {
+            for key in query_param.keys() {
+                if !query_param_keys.insert(key) {
+                    return Err(Error::new(
+                        query_param.field_ident.span(),
+                        format!(
+                            "The query string parameter key \"{}\" is used by several fields of this variant",
+                            key
+                        ),
+                    ));
+                }
+            }
+        }
+
+        for (i, field) in variant.fields.iter().enumerate() {
+            let mut attrs = field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path.is_ident("param"));
+
+            if let Some(attr) = attrs.next() {
+                if let Some(attr) = attrs.next() {
+                    return Err(Error::new(
+                        attr.path.span(),
+                        "The `param` attribute can only be used once on a field",
+                    ));
+                }
+
+                let field_name = match &field.ident {
+                    Some(field_ident) => field_ident.to_string(),
+                    None => i.to_string(),
+                };
+
+                if route.captures_rest(&field_name) {
+                    return Err(Error::new(
+                        attr.path.span(),
+                        "A sub-route or a catch-all param can't have a `with` module, only url params and query params can",
+                    ));
+                }
+
+                if route
+                    .query_rest
+                    .as_ref()
+                    .is_some_and(|ident| *ident == field_name)
+                {
+                    return Err(Error::new(
+                        attr.path.span(),
+                        "A query rest can't have a `with` module",
+                    ));
+                }
+
+                if route.query_params.iter().any(|query_param| {
+                    query_param.kind == QueryParamKind::Flatten
+                        && query_param.field_ident == field_name
+                }) {
+                    return Err(Error::new(
+                        attr.path.span(),
+                        "A flattened query param can't have a `with` module, since it is parsed with serde",
+                    ));
+                }
+
+                if route.query_params.iter().any(|query_param| {
+                    query_param.kind == QueryParamKind::Flag
+                        && query_param.field_ident == field_name
+                }) {
+                    return Err(Error::new(
+                        attr.path.span(),
+                        "A flag query param can't have a `with` module, since its value is its presence",
+                    ));
+                }
+
+                let ParamAttribute { with } = syn::parse2(attr.tokens.clone())?;
+                route.param_modules.insert(field_name, with);
+            }
+        }
+
         Ok(route)
     }
 
@@ -111,6 +534,32 @@ impl Route {
             .iter()
             .any(|localized_route| localized_route.locales.contains(locale))
     }
+
+    /// Returns whether the field named `field_name` (for unnamed fields, the index of the field) is a sub-route or a catch-all
+    /// param.
+    fn captures_rest(&self, field_name: &str) -> bool {
+        self.localized_routes.iter().any(|localized_route| {
+            let path = &localized_route.path;
+            // The unnamed sub-route or catch-all param is always the last field.
+            let is_last_unnamed_field = path.params().count().to_string() == field_name;
+
+            match (&path.sub_route, &path.catch_all) {
+                (Some(SubRoute::Unnamed), _) | (_, Some(CatchAll::Unnamed)) => {
+                    is_last_unnamed_field
+                }
+                (Some(SubRoute::Named(name)), _) | (_, Some(CatchAll::Named(name))) => {
+                    name == field_name
+                }
+                (None, None) => false,
+            }
+        })
+    }
+
+    /// Returns the module defined with `#[param(with = "...")]` for the field named `field_name` (for unnamed fields, the index of
+    /// the field).
+    pub fn param_module(&self, field_name: &str) -> Option<&syn::Path> {
+        self.param_modules.get(field_name)
+    }
 }
 
 fn validate_localized_route(
@@ -137,10 +586,9 @@ fn validate_localized_route(
                         Some(SubRoute::Named(sub_route_name)) => sub_route_name == field_name,
                         None => false,
                     };
-                    let field_is_query_param = field
-                        .attrs
-                        .iter()
-                        .any(|attr| attr.path.is_ident("query_param"));
+                    let field_is_query_param = field.attrs.iter().any(|attr| {
+                        attr.path.is_ident("query_param") || attr.path.is_ident("query_rest")
+                    });
 
                     if !field_is_named_param && !field_is_sub_route && !field_is_query_param {
                         return Err(Error::new(
@@ -162,7 +610,8 @@ fn validate_localized_route(
                 ))
             } else {
                 let number_of_params_and_sub_routes = localized_route.path.params().count()
-                    + localized_route.path.sub_route.is_some() as usize;
+                    + localized_route.path.sub_route.is_some() as usize
+                    + localized_route.path.catch_all.is_some() as usize;
 
                 if number_of_params_and_sub_routes == variant.fields.len() {
                     Ok(())
@@ -181,46 +630,232 @@ fn validate_localized_route(
     }
 }
 
+enum RouteAttribute {
+    /// A path, and the query prefix of its sub-route defined with `query_prefix = "..."`, if there is one.
+    LocalizedRoute(LocalizedRoute, Option<LitStr>),
+    Priority(i32),
+}
+
+impl Parse for RouteAttribute {
+    fn parse(input: &ParseBuffer) -> syn::Result<Self> {
+        let fork = input.fork();
+        let fork_inner;
+        parenthesized!(fork_inner in fork);
+
+        if fork_inner.peek(Ident) && fork_inner.peek2(Token![=]) && !fork_inner.peek2(Token![=>]) {
+            let input_inner;
+            parenthesized!(input_inner in input);
+            helpers::parse_eos(input)?;
+            let ident: Ident = input_inner.parse()?;
+
+            if ident != "priority" {
+                return Err(Error::new(ident.span(), "Expected `priority = ...`"));
+            }
+
+            input_inner.parse::<Token![=]>()?;
+            let is_negative = input_inner.parse::<Option<Token![-]>>()?.is_some();
+            let priority = input_inner.parse::<LitInt>()?.base10_parse::<i32>()?;
+            helpers::parse_eos(&input_inner)?;
+            Ok(Self::Priority(if is_negative {
+                -priority
+            } else {
+                priority
+            }))
+        } else {
+            let input_inner;
+            parenthesized!(input_inner in input);
+            let localized_route = LocalizedRoute::parse_content(&input_inner)?;
+            let mut query_prefix = None;
+
+            if !input_inner.is_empty() {
+                input_inner.parse::<Token![,]>()?;
+                let ident: Ident = input_inner.parse()?;
+
+                if ident != "query_prefix" {
+                    return Err(Error::new(
+                        ident.span(),
+                        "Expected `query_prefix = \"...\"`",
+                    ));
+                }
+
+                input_inner.parse::<Token![=]>()?;
+                query_prefix = Some(input_inner.parse()?);
+            }
+
+            helpers::parse_eos(&input_inner)?;
+            Ok(Self::LocalizedRoute(localized_route, query_prefix))
+        }
+    }
+}
+
+/// `#[param(with = "module")]`, where `module` contains the functions `parse(&str) -> Result<T, E>` and `format(&T) -> String`.
+struct ParamAttribute {
+    with: syn::Path,
+}
+
+impl Parse for ParamAttribute {
+    fn parse(input: &ParseBuffer) -> syn::Result<Self> {
+        let content;
+        parenthesized!(content in input);
+        helpers::parse_eos(input)?;
+        let ident: Ident = content.parse()?;
+
+        if ident != "with" {
+            return Err(Error::new(ident.span(), "Expected `with = \"...\"`"));
+        }
+
+        content.parse::<Token![=]>()?;
+        let with = content.parse::<LitStr>()?.parse()?;
+        helpers::parse_eos(&content)?;
+        Ok(Self { with })
+    }
+}
+
 #[derive(PartialEq, Eq, Debug)]
-pub enum QueryParam {
-    Mandatory(Ident),
-    Optional(Ident),
-    WithDefaultValue(Ident, Expr),
+pub struct QueryParam {
+    pub field_ident: Ident,
+    pub kind: QueryParamKind,
+    /// The key of the query string parameter, defined with `rename = "..."` (the name of the field by default).
+    pub key: String,
+    /// The other keys accepted when parsing, defined with `alias = "..."`.
+    pub aliases: Vec<String>,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum QueryParamKind {
+    Mandatory,
+    Optional,
+    WithDefaultValue(Box<Expr>),
+    /// `#[query_param(multiple)]`, with the separator defined with `separator = "..."`, if there is one.
+    Multiple(Option<String>),
+    /// `#[query_param(flatten)]`: the field is parsed from all the query string parameters with serde_qs.
+    Flatten,
+    /// `#[query_param(flag)]`: the `bool` field is `true` if the query string parameter is present.
+    Flag,
+}
+
+impl QueryParam {
+    /// Returns the keys accepted when parsing: the key, and then the aliases. A flattened query param has no key of its own.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        let keys = match self.kind {
+            QueryParamKind::Flatten => None,
+            _ => Some(iter::once(&self.key).chain(&self.aliases)),
+        };
+
+        keys.into_iter().flatten()
+    }
 }
 
 impl Parse for QueryParam {
     fn parse(input: &ParseBuffer) -> syn::Result<Self> {
         let field_ident: Ident = input.parse()?;
+        let mut query_param = Self {
+            key: field_ident.to_string(),
+            field_ident,
+            kind: QueryParamKind::Mandatory,
+            aliases: Vec::new(),
+        };
 
         if input.is_empty() {
-            Ok(Self::Mandatory(field_ident))
-        } else {
-            let content;
-            parenthesized!(content in input);
-            helpers::parse_eos(input)?;
+            return Ok(query_param);
+        }
+
+        let content;
+        parenthesized!(content in input);
+        helpers::parse_eos(input)?;
+        let mut kind_is_defined = false;
+        let mut rename_is_defined = false;
+        let mut renaming = None;
+        let mut separator = None;
+
+        loop {
             let ident: Ident = content.parse()?;
+            let mut set_kind = |kind| {
+                if kind_is_defined {
+                    return Err(Error::new(
+                        ident.span(),
+                        "Only one of `optional`, `default = ...`, `multiple`, `flatten` and `flag` can be used",
+                    ));
+                }
+
+                query_param.kind = kind;
+                kind_is_defined = true;
+                Ok(())
+            };
 
             if ident == "optional" {
-                helpers::parse_eos(&content)?;
-                Ok(Self::Optional(field_ident))
+                set_kind(QueryParamKind::Optional)?;
             } else if ident == "default" {
                 content.parse::<Token![=]>()?;
-                let default_value = content.parse()?;
-                helpers::parse_eos(&content)?;
-                Ok(Self::WithDefaultValue(field_ident, default_value))
+                set_kind(QueryParamKind::WithDefaultValue(Box::new(content.parse()?)))?;
+            } else if ident == "multiple" {
+                set_kind(QueryParamKind::Multiple(None))?;
+            } else if ident == "flatten" {
+                set_kind(QueryParamKind::Flatten)?;
+            } else if ident == "flag" {
+                set_kind(QueryParamKind::Flag)?;
+            } else if ident == "separator" {
+                content.parse::<Token![=]>()?;
+                let value: LitStr = content.parse()?;
+
+                if value.value().is_empty() {
+                    return Err(Error::new(value.span(), "The separator can't be empty"));
+                }
+
+                separator = Some((ident, value.value()));
+            } else if ident == "rename" {
+                if rename_is_defined {
+                    return Err(Error::new(ident.span(), "`rename` can only be used once"));
+                }
+
+                renaming.get_or_insert(ident.span());
+                content.parse::<Token![=]>()?;
+                query_param.key = content.parse::<LitStr>()?.value();
+                rename_is_defined = true;
+            } else if ident == "alias" {
+                renaming.get_or_insert(ident.span());
+                content.parse::<Token![=]>()?;
+                query_param.aliases.push(content.parse::<LitStr>()?.value());
             } else {
-                Err(Error::new(
+                return Err(Error::new(
                     ident.span(),
-                    "Expected `optional` or `default = ...`",
-                ))
+                    "Expected `optional`, `default = ...`, `multiple`, `flatten`, `flag`, `separator = \"...\"`, `rename = \"...\"` or `alias = \"...\"`",
+                ));
             }
+
+            if content.is_empty() {
+                break;
+            }
+
+            content.parse::<Token![,]>()?;
         }
+
+        if let (QueryParamKind::Flatten, Some(span)) = (&query_param.kind, renaming) {
+            return Err(Error::new(
+                span,
+                "`rename` and `alias` can't be used with `flatten`",
+            ));
+        }
+
+        if let Some((ident, value)) = separator {
+            match &mut query_param.kind {
+                QueryParamKind::Multiple(separator) => *separator = Some(value),
+                _ => {
+                    return Err(Error::new(
+                        ident.span(),
+                        "`separator` can only be used with `multiple`",
+                    ))
+                }
+            }
+        }
+
+        Ok(query_param)
     }
 }
 
 #[test]
 fn test_parsing() {
-    use maplit::hashset;
+    use maplit::{hashmap, hashset};
     use quote::quote;
 
     assert_eq!(
@@ -238,21 +873,26 @@ fn test_parsing() {
                 #[route("/hello/:name/:age")]
                 HelloWithNamedFields {
                     name: String,
+                    #[param(with = "age_format")]
                     age: u8,
                     #[query_param]
                     param: String,
                 },
 
-                #[route("/hello/:/..")]
+                #[route("/hello-sub-route/:/..")]
                 HelloSubRoute(String, SubRoute),
 
-                #[route("/hello/:name/..sub_route")]
+                #[route("/hello-named-sub-route/:name/..sub_route", query_prefix = "sub.")]
                 HelloSubRouteWithNamedFields {
                     #[query_param(default = String::from("default"))]
                     name: String,
                     sub_route: SubRoute,
-                    #[query_param(optional)]
+                    #[query_param(optional, rename = "p", alias = "prm", alias = "parameter")]
                     param: Option<String>,
+                    #[query_param(multiple, alias = "tag")]
+                    tags: Vec<String>,
+                    #[query_param(multiple, separator = ",")]
+                    ids: HashSet<u32>,
                 },
             }
         ))
@@ -269,12 +909,17 @@ fn test_parsing() {
                     path: Path {
                         components: vec![],
                         sub_route: None,
+                        catch_all: None,
                         trailing_slash: true,
                         span: Span::call_site(),
                     },
                     locales: hashset![],
                 }],
                 query_params: vec![],
+                query_rest: None,
+                query_prefix: None,
+                param_modules: HashMap::new(),
+                priority: 0,
             },
             Route {
                 variant: syn::parse2(quote!(
@@ -290,9 +935,10 @@ fn test_parsing() {
                         path: Path {
                             components: vec![
                                 PathComponent::Static(String::from("hello")),
-                                PathComponent::Param(None),
+                                PathComponent::Param(None, None),
                             ],
                             sub_route: None,
+                            catch_all: None,
                             trailing_slash: false,
                             span: Span::call_site(),
                         },
@@ -306,9 +952,10 @@ fn test_parsing() {
                         path: Path {
                             components: vec![
                                 PathComponent::Static(String::from("bonjour")),
-                                PathComponent::Param(None),
+                                PathComponent::Param(None, None),
                             ],
                             sub_route: None,
+                            catch_all: None,
                             trailing_slash: false,
                             span: Span::call_site(),
                         },
@@ -318,9 +965,10 @@ fn test_parsing() {
                         path: Path {
                             components: vec![
                                 PathComponent::Static(String::from("hello")),
-                                PathComponent::Param(None),
+                                PathComponent::Param(None, None),
                             ],
                             sub_route: None,
+                            catch_all: None,
                             trailing_slash: true,
                             span: Span::call_site(),
                         },
@@ -328,12 +976,17 @@ fn test_parsing() {
                     },
                 ],
                 query_params: vec![],
+                query_rest: None,
+                query_prefix: None,
+                param_modules: HashMap::new(),
+                priority: 0,
             },
             Route {
                 variant: syn::parse2(quote!(
                     #[route("/hello/:name/:age")]
                     HelloWithNamedFields {
                         name: String,
+                        #[param(with = "age_format")]
                         age: u8,
                         #[query_param]
                         param: String,
@@ -344,72 +997,344 @@ fn test_parsing() {
                     path: Path {
                         components: vec![
                             PathComponent::Static(String::from("hello")),
-                            PathComponent::Param(Some(String::from("name"))),
-                            PathComponent::Param(Some(String::from("age"))),
+                            PathComponent::Param(Some(String::from("name")), None),
+                            PathComponent::Param(Some(String::from("age")), None),
                         ],
                         sub_route: None,
+                        catch_all: None,
                         trailing_slash: false,
                         span: Span::call_site(),
                     },
                     locales: hashset![],
                 }],
-                query_params: vec![QueryParam::Mandatory(Ident::new(
-                    "param",
-                    Span::call_site()
-                ))],
+                query_params: vec![QueryParam {
+                    field_ident: Ident::new("param", Span::call_site()),
+                    kind: QueryParamKind::Mandatory,
+                    key: String::from("param"),
+                    aliases: vec![],
+                }],
+                query_rest: None,
+                query_prefix: None,
+                param_modules: hashmap! {
+                    String::from("age") => syn::parse2(quote!(age_format)).unwrap(),
+                },
+                priority: 0,
             },
             Route {
                 variant: syn::parse2(quote!(
-                    #[route("/hello/:/..")]
+                    #[route("/hello-sub-route/:/..")]
                     HelloSubRoute(String, SubRoute)
                 ))
                 .unwrap(),
                 localized_routes: vec![LocalizedRoute {
                     path: Path {
                         components: vec![
-                            PathComponent::Static(String::from("hello")),
-                            PathComponent::Param(None),
+                            PathComponent::Static(String::from("hello-sub-route")),
+                            PathComponent::Param(None, None),
                         ],
                         sub_route: Some(SubRoute::Unnamed),
+                        catch_all: None,
                         trailing_slash: false,
                         span: Span::call_site(),
                     },
                     locales: hashset![],
                 }],
                 query_params: vec![],
+                query_rest: None,
+                query_prefix: None,
+                param_modules: HashMap::new(),
+                priority: 0,
             },
             Route {
                 variant: syn::parse2(quote!(
-                    #[route("/hello/:name/..sub_route")]
+                    #[route("/hello-named-sub-route/:name/..sub_route", query_prefix = "sub.")]
                     HelloSubRouteWithNamedFields {
                         #[query_param(default = String::from("default"))]
                         name: String,
                         sub_route: SubRoute,
-                        #[query_param(optional)]
+                        #[query_param(optional, rename = "p", alias = "prm", alias = "parameter")]
                         param: Option<String>,
+                        #[query_param(multiple, alias = "tag")]
+                        tags: Vec<String>,
+                        #[query_param(multiple, separator = ",")]
+                        ids: HashSet<u32>,
                     }
                 ))
                 .unwrap(),
                 localized_routes: vec![LocalizedRoute {
                     path: Path {
                         components: vec![
-                            PathComponent::Static(String::from("hello")),
-                            PathComponent::Param(Some(String::from("name"))),
+                            PathComponent::Static(String::from("hello-named-sub-route")),
+                            PathComponent::Param(Some(String::from("name")), None),
                         ],
                         sub_route: Some(SubRoute::Named(String::from("sub_route"))),
+                        catch_all: None,
                         trailing_slash: false,
                         span: Span::call_site(),
                     },
                     locales: hashset![],
                 }],
                 query_params: vec![
-                    QueryParam::WithDefaultValue(
-                        Ident::new("name", Span::call_site()),
-                        syn::parse2(quote!(String::from("default"))).unwrap()
-                    ),
-                    QueryParam::Optional(Ident::new("param", Span::call_site()))
+                    QueryParam {
+                        field_ident: Ident::new("name", Span::call_site()),
+                        kind: QueryParamKind::WithDefaultValue(Box::new(
+                            syn::parse2(quote!(String::from("default"))).unwrap()
+                        )),
+                        key: String::from("name"),
+                        aliases: vec![],
+                    },
+                    QueryParam {
+                        field_ident: Ident::new("param", Span::call_site()),
+                        kind: QueryParamKind::Optional,
+                        key: String::from("p"),
+                        aliases: vec![String::from("prm"), String::from("parameter")],
+                    },
+                    QueryParam {
+                        field_ident: Ident::new("tags", Span::call_site()),
+                        kind: QueryParamKind::Multiple(None),
+                        key: String::from("tags"),
+                        aliases: vec![String::from("tag")],
+                    },
+                    QueryParam {
+                        field_ident: Ident::new("ids", Span::call_site()),
+                        kind: QueryParamKind::Multiple(Some(String::from(","))),
+                        key: String::from("ids"),
+                        aliases: vec![],
+                    },
                 ],
+                query_rest: None,
+                query_prefix: Some(String::from("sub.")),
+                param_modules: HashMap::new(),
+                priority: 0,
             },
         ]
     );
 }
+
+#[test]
+fn test_ambiguities() {
+    use quote::quote;
+
+    fn error_messages(item: TokenStream) -> Vec<String> {
+        match Router::parse(item) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors.into_iter().map(|error| error.to_string()).collect(),
+        }
+    }
+
+    assert_eq!(
+        error_messages(quote!(
+            enum Router {
+                #[route("/hello/:")]
+                Hello(String),
+
+                #[route("/hello/about")]
+                About,
+            }
+        )),
+        vec![
+            "This route is ambiguous: some urls can be matched both by \"/hello/:\" and by \"/hello/about\", for the same locales \
+            (use `#[route(priority = ...)]` to choose which one is tried first)",
+            "\"/hello/:\" is defined here",
+        ],
+    );
+
+    assert_eq!(
+        error_messages(quote!(
+            enum Router {
+                #[route(en, fr => "/about")]
+                About,
+
+                #[route(fr => "/about")]
+                #[route(de => "/a-propos")]
+                AnotherAbout,
+            }
+        )),
+        vec![
+            "This route is ambiguous: some urls can be matched both by \"/about\" and by \"/about\", for the same locales \
+            (use `#[route(priority = ...)]` to choose which one is tried first)",
+            "\"/about\" is defined here",
+        ],
+    );
+
+    assert_eq!(
+        error_messages(quote!(
+            enum Router {
+                #[route("/sub-route/..")]
+                WithSubRoute(SubRoute),
+
+                #[route(en => "/sub-route/about")]
+                About,
+            }
+        ))
+        .len(),
+        2,
+    );
+
+    // No ambiguity between different locales
+    assert_eq!(
+        error_messages(quote!(
+            enum Router {
+                #[route(en => "/about")]
+                About,
+
+                #[route(fr => "/about")]
+                AnotherAbout,
+
+                #[route(en => "/hello/:")]
+                Hello(String),
+
+                #[route(fr => "/hello/about")]
+                FrenchAbout,
+            }
+        )),
+        Vec::<String>::new(),
+    );
+
+    // No ambiguity when a constraint excludes the static segment
+    assert_eq!(
+        error_messages(quote!(
+            enum Router {
+                #[route("/post/new")]
+                NewPost,
+
+                #[route(r"/post/:<\d+>")]
+                Post(u32),
+            }
+        )),
+        Vec::<String>::new(),
+    );
+
+    assert_eq!(
+        error_messages(quote!(
+            enum Router {
+                #[route("/archive(/:)?(/:)?")]
+                Archive(Option<u16>, Option<u8>),
+            }
+        )),
+        vec![
+            "This route is ambiguous: its optional groups can produce the paths \"/archive/:\" and \"/archive/:\", which can match \
+            the same urls (add static segments to distinguish them)",
+        ],
+    );
+
+    // A sub-route or a catch-all param can capture the segments of an omitted optional group.
+    assert_eq!(
+        error_messages(quote!(
+            enum Router {
+                #[route("/a(/:x)?/..sub")]
+                A { x: Option<String>, sub: SubRoute },
+
+                #[route("/b(/:x)?/*rest")]
+                B { x: Option<String>, rest: String },
+
+                #[route(r"/c(/:x<\d+>)?/list/..sub")]
+                C { x: Option<u32>, sub: SubRoute },
+            }
+        )),
+        vec![
+            "This route is ambiguous: its optional groups can produce the paths \"/a/:x/..sub\" and \"/a/..sub\", which can \
+            match the same urls (add static segments to distinguish them)",
+            "This route is ambiguous: its optional groups can produce the paths \"/b/:x/*rest\" and \"/b/*rest\", which can \
+            match the same urls (add static segments to distinguish them)",
+        ],
+    );
+}
+
+#[test]
+fn test_parsing_options() {
+    use quote::quote;
+
+    fn options(item: TokenStream) -> Result<RouterOptions, String> {
+        Router::parse(item)
+            .map(|router| router.options)
+            .map_err(|error| error.to_string())
+    }
+
+    assert_eq!(
+        options(quote!(
+            enum Router {
+                #[route("/")]
+                Home,
+            }
+        )),
+        Ok(RouterOptions {
+            trailing_slash: TrailingSlash::Strict,
+            case_insensitive: false,
+            unknown_query: UnknownQuery::Ignore,
+            query_order: QueryOrder::Tree,
+        }),
+    );
+
+    assert_eq!(
+        options(quote!(
+            #[chemin(
+                trailing_slash = "redirect",
+                case_insensitive,
+                unknown_query = "capture",
+                query_order = "declaration"
+            )]
+            enum Router {
+                #[route("/")]
+                Home,
+            }
+        )),
+        Ok(RouterOptions {
+            trailing_slash: TrailingSlash::Redirect,
+            case_insensitive: true,
+            unknown_query: UnknownQuery::Capture,
+            query_order: QueryOrder::Declaration,
+        }),
+    );
+
+    assert_eq!(
+        options(quote!(
+            #[chemin(trailing_slash = "sometimes")]
+            enum Router {
+                #[route("/")]
+                Home,
+            }
+        )),
+        Err(String::from(
+            "Expected \"strict\", \"ignore\" or \"redirect\""
+        )),
+    );
+
+    assert_eq!(
+        options(quote!(
+            #[chemin(trailing_slash = "ignore")]
+            #[chemin(trailing_slash = "strict")]
+            enum Router {
+                #[route("/")]
+                Home,
+            }
+        )),
+        Err(String::from(
+            "The trailing slash policy can only be defined once"
+        )),
+    );
+
+    assert_eq!(
+        options(quote!(
+            #[chemin(unknown_query = "keep")]
+            enum Router {
+                #[route("/")]
+                Home,
+            }
+        )),
+        Err(String::from(
+            "Expected \"ignore\", \"reject\" or \"capture\""
+        )),
+    );
+
+    assert_eq!(
+        options(quote!(
+            #[chemin(query_order = "alphabetical", query_order = "tree")]
+            enum Router {
+                #[route("/")]
+                Home,
+            }
+        )),
+        Err(String::from("The query order can only be defined once")),
+    );
+}
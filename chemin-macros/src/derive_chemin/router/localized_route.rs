@@ -1,9 +1,12 @@
 use crate::helpers;
+use pest::error::ErrorVariant;
 use pest::iterators::Pair;
 use pest::Parser;
 use pest_derive::Parser;
 use proc_macro2::Span;
+use regex::Regex;
 use std::collections::HashSet;
+use std::fmt::{self, Display, Formatter};
 use syn::ext::IdentExt;
 use syn::parse::{Parse, ParseBuffer};
 use syn::punctuated::Punctuated;
@@ -15,26 +18,36 @@ pub struct LocalizedRoute {
     pub locales: HashSet<String>,
 }
 
-impl Parse for LocalizedRoute {
-    fn parse(input: &ParseBuffer) -> syn::Result<Self> {
-        let input_inner;
-        parenthesized!(input_inner in input);
+impl LocalizedRoute {
+    /// Whether some url could be matched by both routes, for a common locale. See [Path::can_match_same_url_as] for
+    /// `case_insensitive`.
+    pub fn overlaps(&self, other: &LocalizedRoute, case_insensitive: bool) -> syn::Result<bool> {
+        let locales_overlap = self.locales.is_empty()
+            || other.locales.is_empty()
+            || !self.locales.is_disjoint(&other.locales);
 
-        if input_inner.peek(LitStr) {
-            let path: Path = input_inner.parse()?;
-            input_inner.call(helpers::parse_eos)?;
+        Ok(locales_overlap
+            && self
+                .path
+                .can_match_same_url_as(&other.path, case_insensitive)?)
+    }
+}
+
+impl LocalizedRoute {
+    /// Parses `"/path"` or `locale, ... => "/path"`, without the parentheses of the `#[route]` attribute, leaving what follows the
+    /// path.
+    pub fn parse_content(input: &ParseBuffer) -> syn::Result<Self> {
+        if input.peek(LitStr) {
             Ok(Self {
-                path,
+                path: input.parse()?,
                 locales: HashSet::new(),
             })
         } else {
             let locales: Punctuated<Ident, Token![,]> =
-                Punctuated::parse_separated_nonempty_with(&input_inner, Ident::parse_any)?;
-            input_inner.parse::<Token![=>]>()?;
-            let path: Path = input_inner.parse()?;
-            input_inner.call(helpers::parse_eos)?;
+                Punctuated::parse_separated_nonempty_with(input, Ident::parse_any)?;
+            input.parse::<Token![=>]>()?;
             Ok(Self {
-                path,
+                path: input.parse()?,
                 locales: locales
                     .into_iter()
                     .map(|locale_ident| locale_ident.to_string().replace('_', "-"))
@@ -44,11 +57,23 @@ impl Parse for LocalizedRoute {
     }
 }
 
-#[derive(Debug)]
+impl Parse for LocalizedRoute {
+    fn parse(input: &ParseBuffer) -> syn::Result<Self> {
+        let input_inner;
+        parenthesized!(input_inner in input);
+        let localized_route = Self::parse_content(&input_inner)?;
+        input_inner.call(helpers::parse_eos)?;
+        Ok(localized_route)
+    }
+}
+
+#[derive(Clone, Debug)]
 pub struct Path {
     pub components: Vec<PathComponent>,
     /// `None` if there is no sub-route, `Some(None)` if there is a unnamed sub-route, `Some(Some)` if there is a named sub-route.
     pub sub_route: Option<SubRoute>,
+    /// The catch-all param (as in `"/static/*path"`), capturing the rest of the url, if there is one.
+    pub catch_all: Option<CatchAll>,
     pub trailing_slash: bool,
     pub span: Span,
 }
@@ -57,6 +82,7 @@ impl PartialEq for Path {
     fn eq(&self, other: &Self) -> bool {
         self.components == other.components
             && self.sub_route == other.sub_route
+            && self.catch_all == other.catch_all
             && self.trailing_slash == other.trailing_slash
     }
 }
@@ -66,44 +92,217 @@ impl Eq for Path {}
 impl Path {
     pub fn contains_unnamed_params_and_sub_routes(&self) -> bool {
         matches!(self.sub_route, Some(SubRoute::Unnamed))
-            || self
-                .components
-                .iter()
-                .any(|path_component| match path_component {
-                    PathComponent::Static(_) => false,
-                    PathComponent::Param(name) => name.is_none(),
-                })
+            || matches!(self.catch_all, Some(CatchAll::Unnamed))
+            || self.params().any(|name| name.is_none())
     }
 
     pub fn contains_named_params_and_sub_routes(&self) -> bool {
         matches!(self.sub_route, Some(SubRoute::Named(_)))
-            || self
-                .components
-                .iter()
-                .any(|path_component| match path_component {
-                    PathComponent::Static(_) => false,
-                    PathComponent::Param(name) => name.is_some(),
-                })
+            || matches!(self.catch_all, Some(CatchAll::Named(_)))
+            || self.params().any(|name| name.is_some())
     }
 
+    /// Returns the names of the params (excluding the catch-all param), including the ones inside optional groups and mixed
+    /// segments.
     pub fn params(&self) -> impl Iterator<Item = Option<&String>> {
-        self.components
+        self.components.iter().flat_map(PathComponent::params)
+    }
+
+    /// Whether the param at index `i` (as returned by [Path::params]) is inside an optional group.
+    pub fn is_optional_param(&self, i: usize) -> bool {
+        self.forms()
             .iter()
-            .filter_map(|path_component| match path_component {
-                PathComponent::Static(_) => None,
-                PathComponent::Param(name) => Some(name),
-            })
-            .map(|param| param.as_ref())
+            .any(|form| !form.param_indices.contains(&i))
     }
 
-    pub fn has_named_param(&self, expected_name: &str) -> bool {
-        self.components
+    /// Returns the forms of the path, where each optional group is either included or omitted, sorted by decreasing number of
+    /// components (the first form includes every group and the last one omits every group).
+    pub fn forms(&self) -> Vec<PathForm> {
+        let groups_count = self
+            .components
             .iter()
-            .any(|path_component| match path_component {
-                PathComponent::Static(_) => false,
-                PathComponent::Param(None) => false,
-                PathComponent::Param(Some(name)) => name == expected_name,
+            .filter(|path_component| matches!(path_component, PathComponent::Optional(_)))
+            .count();
+
+        let mut forms = (0..1usize << groups_count)
+            .map(|omitted_groups| {
+                let mut form = PathForm {
+                    path: Path {
+                        components: Vec::new(),
+                        ..self.clone()
+                    },
+                    param_indices: Vec::new(),
+                };
+                let mut group_i = 0;
+                let mut param_i = 0;
+
+                for path_component in &self.components {
+                    let (group, included) = match path_component {
+                        PathComponent::Optional(group) => {
+                            let included = omitted_groups & (1 << group_i) == 0;
+                            group_i += 1;
+                            (&group[..], included)
+                        }
+                        _ => (std::slice::from_ref(path_component), true),
+                    };
+
+                    for path_component in group {
+                        let params_count = path_component.params().len();
+
+                        if included {
+                            form.param_indices.extend(param_i..param_i + params_count);
+                            form.path.components.push(path_component.clone());
+                        }
+
+                        param_i += params_count;
+                    }
+                }
+
+                form
             })
+            .collect::<Vec<_>>();
+
+        // The sort is stable, so forms with the same number of components stay ordered by omitted groups.
+        forms.sort_by_key(|form| std::cmp::Reverse(form.path.components.len()));
+        forms
+    }
+
+    /// Returns two forms of the path (see [Path::forms]) which could match the same url, if there are some. A url generated with one
+    /// of them could then be parsed with the other one, with different values for the fields. Without a sub-route or a catch-all
+    /// param, forms with different numbers of components are not ambiguous, since the longest ones are tried first when parsing.
+    /// With one of them, the rest of the url captured by a shorter form could match the additional components of a longer form, so
+    /// the components of the shorter form are compared with the first components of the longer one.
+    pub fn ambiguous_forms(
+        &self,
+        case_insensitive: bool,
+    ) -> syn::Result<Option<(PathForm, PathForm)>> {
+        let mut forms = self.forms();
+
+        for i in 0..forms.len() {
+            for j in i + 1..forms.len() {
+                let (a, b) = (&forms[i].path, &forms[j].path);
+
+                if (a.components.len() == b.components.len() || self.captures_rest())
+                    && a.components_can_match_same_segments_as(b, case_insensitive)?
+                {
+                    let b = forms.swap_remove(j);
+                    return Ok(Some((forms.swap_remove(i), b)));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Whether some url could be matched by both paths. If `case_insensitive` is `true`, static segments are compared
+    /// case-insensitively.
+    pub fn can_match_same_url_as(&self, other: &Path, case_insensitive: bool) -> syn::Result<bool> {
+        for form in self.forms() {
+            for other_form in other.forms() {
+                if form
+                    .path
+                    .form_can_match_same_url_as(&other_form.path, case_insensitive)?
+                {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Whether some url could be matched by both paths, which don't have optional groups.
+    fn form_can_match_same_url_as(
+        &self,
+        other: &Path,
+        case_insensitive: bool,
+    ) -> syn::Result<bool> {
+        if !self.components_can_match_same_segments_as(other, case_insensitive)? {
+            return Ok(false);
+        }
+
+        // A sub-route or a catch-all param matches any non-empty rest of the url.
+        let matches_rest_of = |path: &Path, other: &Path| {
+            path.captures_rest()
+                && (other.components.len() > path.components.len() || other.captures_rest())
+        };
+
+        Ok(matches_rest_of(self, other)
+            || matches_rest_of(other, self)
+            || (!self.captures_rest()
+                && !other.captures_rest()
+                && self.components.len() == other.components.len()
+                && self.trailing_slash == other.trailing_slash))
+    }
+
+    /// Whether each component of the path could match the same url segment as the component of `other` at the same position (as
+    /// far as both paths go).
+    fn components_can_match_same_segments_as(
+        &self,
+        other: &Path,
+        case_insensitive: bool,
+    ) -> syn::Result<bool> {
+        for (a, b) in self.components.iter().zip(&other.components) {
+            let can_match_same_segment =
+                a.can_match_same_segment_as(b, case_insensitive)
+                    .map_err(|regex_error| {
+                        Error::new(self.span, format!("invalid constraint: {}", regex_error))
+                    })?;
+
+            if !can_match_same_segment {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Whether the rest of the url is captured by a sub-route or a catch-all param.
+    fn captures_rest(&self) -> bool {
+        self.sub_route.is_some() || self.catch_all.is_some()
+    }
+
+    /// Whether the path has a param (including the catch-all param) named `expected_name`.
+    pub fn has_named_param(&self, expected_name: &str) -> bool {
+        matches!(&self.catch_all, Some(CatchAll::Named(name)) if name == expected_name)
+            || self
+                .params()
+                .any(|name| name.map(String::as_str) == Some(expected_name))
+    }
+}
+
+/// A form of a path, where each optional group is either included or omitted. See [Path::forms].
+pub struct PathForm {
+    /// The path, without optional groups.
+    pub path: Path,
+    /// For each param of `path`, its index among the params of the complete path (as returned by [Path::params]).
+    pub param_indices: Vec<usize>,
+}
+
+impl Display for Path {
+    /// Displays the path with the syntax of the `#[route]` attribute.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for component in &self.components {
+            write!(f, "{}", component)?;
+        }
+
+        match &self.sub_route {
+            Some(SubRoute::Unnamed) => write!(f, "/..")?,
+            Some(SubRoute::Named(name)) => write!(f, "/..{}", name)?,
+            None => (),
+        }
+
+        match &self.catch_all {
+            Some(CatchAll::Unnamed) => write!(f, "/*")?,
+            Some(CatchAll::Named(name)) => write!(f, "/*{}", name)?,
+            None => (),
+        }
+
+        if self.trailing_slash {
+            write!(f, "/")?;
+        }
+
+        Ok(())
     }
 }
 
@@ -135,14 +334,19 @@ impl Path {
                 let mut path = Self {
                     components: Vec::new(),
                     sub_route: None,
+                    catch_all: None,
                     trailing_slash: false,
                     span: Span::call_site(),
                 };
 
                 for pair in path_pair.into_inner() {
                     match pair.as_rule() {
-                        Rule::static_path | Rule::param => path.components.push(pair.into()),
+                        Rule::static_path | Rule::param | Rule::mixed | Rule::optional_group => {
+                            path.components.push(PathComponent::parse_pair(pair)?)
+                        }
+
                         Rule::sub_route => path.sub_route = Some(pair.into()),
+                        Rule::catch_all => path.catch_all = Some(pair.into()),
                         Rule::trailing_slash => path.trailing_slash = true,
                         Rule::EOI => break,
                         _ => unreachable!(),
@@ -157,10 +361,182 @@ impl Path {
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum PathComponent {
     Static(String),
-    Param(Option<String>),
+    /// A param, with its name if it is named, and its constraint (a regex which the whole segment must match) if it has one.
+    Param(Option<String>, Option<String>),
+    /// An optional group (as in `"/blog(/page/:page)?"`), containing static, param and mixed components, with exactly one param in
+    /// total.
+    Optional(Vec<PathComponent>),
+    /// A segment mixing static text and params (as in `"/file-:id.json"`), with its static and param parts.
+    Mixed(Vec<PathComponent>),
+}
+
+impl PathComponent {
+    /// Returns the names of the params of this component.
+    fn params(&self) -> Vec<Option<&String>> {
+        match self {
+            Self::Static(_) => Vec::new(),
+            Self::Param(name, _) => vec![name.as_ref()],
+            Self::Optional(components) | Self::Mixed(components) => {
+                components.iter().flat_map(Self::params).collect()
+            }
+        }
+    }
+
+    /// Returns the regex matching the whole segment of a mixed component, where each param is captured by a group named
+    /// `__chemin_param_{i}`.
+    pub fn mixed_segment_regex(parts: &[PathComponent]) -> String {
+        let mut regex = String::new();
+        let mut param_i = 0;
+
+        for part in parts {
+            match part {
+                Self::Static(value) => regex.push_str(&regex::escape(value)),
+
+                Self::Param(_, constraint) => {
+                    regex.push_str(&format!(
+                        "(?P<__chemin_param_{}>{})",
+                        param_i,
+                        match constraint {
+                            Some(constraint) => format!("(?:{})", constraint),
+                            None => String::from("[^/]+?"),
+                        }
+                    ));
+                    param_i += 1;
+                }
+
+                Self::Optional(_) | Self::Mixed(_) => unreachable!(),
+            }
+        }
+
+        regex
+    }
+
+    fn parse_pair(pair: Pair<Rule>) -> Result<Self, Box<pest::error::Error<Rule>>> {
+        let span = pair.as_span();
+        let error = |message: String| {
+            Box::new(pest::error::Error::new_from_span(
+                ErrorVariant::CustomError { message },
+                span,
+            ))
+        };
+
+        match pair.as_rule() {
+            Rule::optional_group => {
+                let group = pair
+                    .into_inner()
+                    .map(Self::parse_pair)
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if group.iter().flat_map(Self::params).count() != 1 {
+                    return Err(error(String::from(
+                        "an optional group must contain exactly one param",
+                    )));
+                }
+
+                Ok(Self::Optional(group))
+            }
+
+            Rule::mixed => {
+                let parts = pair
+                    .into_inner()
+                    .map(Self::parse_pair)
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                if let Err(regex_error) = anchored_regex(&Self::mixed_segment_regex(&parts)) {
+                    return Err(error(format!("invalid constraint: {}", regex_error)));
+                }
+
+                Ok(Self::Mixed(parts))
+            }
+
+            _ => {
+                let component = Self::from(pair);
+
+                if let Self::Param(_, Some(constraint)) = &component {
+                    if let Err(regex_error) = anchored_regex(constraint) {
+                        return Err(error(format!("invalid constraint: {}", regex_error)));
+                    }
+                }
+
+                Ok(component)
+            }
+        }
+    }
+
+    /// Whether some url segment could be matched by both components. Two params are always considered compatible, even if both
+    /// have a constraint.
+    fn can_match_same_segment_as(
+        &self,
+        other: &PathComponent,
+        case_insensitive: bool,
+    ) -> Result<bool, regex::Error> {
+        Ok(match (self, other) {
+            (Self::Static(a), Self::Static(b)) => {
+                a == b || (case_insensitive && a.eq_ignore_ascii_case(b))
+            }
+
+            (Self::Static(value), Self::Param(_, Some(constraint)))
+            | (Self::Param(_, Some(constraint)), Self::Static(value)) => {
+                anchored_regex(constraint)?.is_match(value)
+            }
+
+            (Self::Static(value), Self::Mixed(parts))
+            | (Self::Mixed(parts), Self::Static(value)) => {
+                anchored_regex(&Self::mixed_segment_regex(parts))?.is_match(value)
+            }
+
+            (Self::Optional(_), _) | (_, Self::Optional(_)) => unreachable!(),
+            _ => true,
+        })
+    }
+}
+
+/// Compiles the regex of a constraint (or of a mixed segment) the same way as `chemin::Constraint` does at runtime, so that a
+/// constraint accepted at compile time can't fail to compile when parsing a url.
+fn anchored_regex(source: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("^(?:{})$", source))
+}
+
+impl Display for PathComponent {
+    /// Displays the component with the syntax of the `#[route]` attribute.
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::Static(value) => write!(f, "/{}", value),
+
+            Self::Param(name, constraint) => {
+                write!(f, "/:{}", name.as_deref().unwrap_or(""))?;
+
+                match constraint {
+                    Some(constraint) => write!(f, "<{}>", constraint),
+                    None => Ok(()),
+                }
+            }
+
+            Self::Optional(group) => {
+                write!(f, "(")?;
+
+                for component in group {
+                    write!(f, "{}", component)?;
+                }
+
+                write!(f, ")?")
+            }
+
+            Self::Mixed(parts) => {
+                write!(f, "/")?;
+
+                for part in parts {
+                    // Each part is displayed without its leading slash.
+                    write!(f, "{}", &part.to_string()[1..])?;
+                }
+
+                Ok(())
+            }
+        }
+    }
 }
 
 impl From<Pair<'_, Rule>> for PathComponent {
@@ -168,21 +544,52 @@ impl From<Pair<'_, Rule>> for PathComponent {
         match pair.as_rule() {
             Rule::static_path => Self::Static(pair.as_str().to_owned()),
 
-            Rule::param => match pair.into_inner().next() {
-                Some(field_pair) => {
-                    assert_eq!(field_pair.as_rule(), Rule::field);
-                    Self::Param(Some(validate_ident(field_pair.as_str()).to_owned()))
+            Rule::param => {
+                let mut name = None;
+                let mut constraint = None;
+
+                for inner_pair in pair.into_inner() {
+                    match inner_pair.as_rule() {
+                        Rule::field => name = Some(validate_ident(inner_pair.as_str()).to_owned()),
+
+                        Rule::constraint => {
+                            let regex_pair = inner_pair.into_inner().next().unwrap();
+                            assert_eq!(regex_pair.as_rule(), Rule::regex);
+                            constraint = Some(regex_pair.as_str().to_owned());
+                        }
+
+                        _ => unreachable!(),
+                    }
                 }
 
-                None => Self::Param(None),
-            },
+                Self::Param(name, constraint)
+            }
 
             _ => unreachable!(),
         }
     }
 }
 
-#[derive(PartialEq, Eq, Debug)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum CatchAll {
+    Unnamed,
+    Named(String),
+}
+
+impl From<Pair<'_, Rule>> for CatchAll {
+    fn from(pair: Pair<'_, Rule>) -> Self {
+        match pair.into_inner().next() {
+            Some(field_pair) => {
+                assert_eq!(field_pair.as_rule(), Rule::field);
+                Self::Named(validate_ident(field_pair.as_str()).to_owned())
+            }
+
+            None => Self::Unnamed,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Eq, Debug)]
 pub enum SubRoute {
     Unnamed,
     Named(String),
@@ -214,6 +621,7 @@ fn test_path_parsing() {
         Ok(Path {
             components: vec![PathComponent::Static(String::from("home"))],
             sub_route: None,
+            catch_all: None,
             trailing_slash: false,
             span: Span::call_site(),
         })
@@ -224,6 +632,7 @@ fn test_path_parsing() {
         Ok(Path {
             components: vec![PathComponent::Static(String::from("home"))],
             sub_route: None,
+            catch_all: None,
             trailing_slash: true,
             span: Span::call_site(),
         })
@@ -234,9 +643,10 @@ fn test_path_parsing() {
         Ok(Path {
             components: vec![
                 PathComponent::Static(String::from("hello")),
-                PathComponent::Param(None),
+                PathComponent::Param(None, None),
             ],
             sub_route: None,
+            catch_all: None,
             trailing_slash: false,
             span: Span::call_site(),
         })
@@ -247,11 +657,12 @@ fn test_path_parsing() {
         Ok(Path {
             components: vec![
                 PathComponent::Static(String::from("hello")),
-                PathComponent::Param(Some(String::from("name"))),
-                PathComponent::Param(Some(String::from("age"))),
+                PathComponent::Param(Some(String::from("name")), None),
+                PathComponent::Param(Some(String::from("age")), None),
                 PathComponent::Static(String::from("aaa")),
             ],
             sub_route: Some(SubRoute::Named(String::from("rest"))),
+            catch_all: None,
             trailing_slash: false,
             span: Span::call_site(),
         })
@@ -262,11 +673,418 @@ fn test_path_parsing() {
         Ok(Path {
             components: vec![
                 PathComponent::Static(String::from("hello")),
-                PathComponent::Param(None),
+                PathComponent::Param(None, None),
+            ],
+            sub_route: Some(SubRoute::Unnamed),
+            catch_all: None,
+            trailing_slash: false,
+            span: Span::call_site(),
+        })
+    );
+}
+
+#[test]
+fn test_path_parsing_catch_all() {
+    assert_eq!(
+        Path::parse_str("/static/*path"),
+        Ok(Path {
+            components: vec![PathComponent::Static(String::from("static"))],
+            sub_route: None,
+            catch_all: Some(CatchAll::Named(String::from("path"))),
+            trailing_slash: false,
+            span: Span::call_site(),
+        })
+    );
+
+    assert_eq!(
+        Path::parse_str("/:/*"),
+        Ok(Path {
+            components: vec![PathComponent::Param(None, None)],
+            sub_route: None,
+            catch_all: Some(CatchAll::Unnamed),
+            trailing_slash: false,
+            span: Span::call_site(),
+        })
+    );
+
+    // Only a final segment starting with "*" is a catch-all, "*" being a static character elsewhere.
+    assert_eq!(
+        Path::parse_str("/static/*path/about"),
+        Ok(Path {
+            components: vec![
+                PathComponent::Static(String::from("static")),
+                PathComponent::Static(String::from("*path")),
+                PathComponent::Static(String::from("about")),
+            ],
+            sub_route: None,
+            catch_all: None,
+            trailing_slash: false,
+            span: Span::call_site(),
+        })
+    );
+    assert_eq!(
+        Path::parse_str("/*/"),
+        Ok(Path {
+            components: vec![PathComponent::Static(String::from("*"))],
+            sub_route: None,
+            catch_all: None,
+            trailing_slash: true,
+            span: Span::call_site(),
+        })
+    );
+    assert_eq!(
+        Path::parse_str("/static/*path/.."),
+        Ok(Path {
+            components: vec![
+                PathComponent::Static(String::from("static")),
+                PathComponent::Static(String::from("*path")),
             ],
             sub_route: Some(SubRoute::Unnamed),
+            catch_all: None,
+            trailing_slash: false,
+            span: Span::call_site(),
+        })
+    );
+    assert_eq!(
+        Path::parse_str("/static/*.css"),
+        Ok(Path {
+            components: vec![
+                PathComponent::Static(String::from("static")),
+                PathComponent::Static(String::from("*.css")),
+            ],
+            sub_route: None,
+            catch_all: None,
+            trailing_slash: false,
+            span: Span::call_site(),
+        })
+    );
+}
+
+#[test]
+fn test_path_parsing_optional_groups() {
+    assert_eq!(
+        Path::parse_str("/blog(/page/:page)?(/:<[a-z]+>)?/"),
+        Ok(Path {
+            components: vec![
+                PathComponent::Static(String::from("blog")),
+                PathComponent::Optional(vec![
+                    PathComponent::Static(String::from("page")),
+                    PathComponent::Param(Some(String::from("page")), None),
+                ]),
+                PathComponent::Optional(vec![PathComponent::Param(
+                    None,
+                    Some(String::from("[a-z]+"))
+                )]),
+            ],
+            sub_route: None,
+            catch_all: None,
+            trailing_slash: true,
+            span: Span::call_site(),
+        })
+    );
+
+    // Parentheses which don't delimit an optional group are part of the static segments
+    assert_eq!(
+        Path::parse_str("/wiki/Rust_(language)"),
+        Ok(Path {
+            components: vec![
+                PathComponent::Static(String::from("wiki")),
+                PathComponent::Static(String::from("Rust_(language)")),
+            ],
+            sub_route: None,
+            catch_all: None,
+            trailing_slash: false,
+            span: Span::call_site(),
+        })
+    );
+
+    assert!(Path::parse_str("/blog(/page)?").is_err());
+    assert!(Path::parse_str("/blog(/:year/:month)?").is_err());
+    assert!(Path::parse_str("/blog(/page/:page)").is_err());
+    assert!(Path::parse_str("/blog(/(/:page)?)?").is_err());
+}
+
+#[test]
+fn test_path_forms() {
+    let forms = |path: &str| {
+        Path::parse_str(path)
+            .unwrap()
+            .forms()
+            .into_iter()
+            .map(|form| (form.path.to_string(), form.param_indices))
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(forms("/hello/:"), vec![(String::from("/hello/:"), vec![0])]);
+    assert_eq!(
+        forms("/:a(/page/:b)?/:c(/:d)?/.."),
+        vec![
+            (String::from("/:a/page/:b/:c/:d/.."), vec![0, 1, 2, 3]),
+            (String::from("/:a/page/:b/:c/.."), vec![0, 1, 2]),
+            (String::from("/:a/:c/:d/.."), vec![0, 2, 3]),
+            (String::from("/:a/:c/.."), vec![0, 2]),
+        ]
+    );
+    assert_eq!(
+        forms("/:a(/:b)?(/x/y/:c)?"),
+        vec![
+            (String::from("/:a/:b/x/y/:c"), vec![0, 1, 2]),
+            (String::from("/:a/x/y/:c"), vec![0, 2]),
+            (String::from("/:a/:b"), vec![0, 1]),
+            (String::from("/:a"), vec![0]),
+        ]
+    );
+}
+
+#[test]
+fn test_path_ambiguous_forms() {
+    let ambiguous_forms = |path: &str| {
+        Path::parse_str(path)
+            .unwrap()
+            .ambiguous_forms(false)
+            .unwrap()
+            .map(|(a, b)| (a.path.to_string(), b.path.to_string()))
+    };
+
+    assert_eq!(
+        ambiguous_forms("/archive(/:)?(/:)?"),
+        Some((String::from("/archive/:"), String::from("/archive/:")))
+    );
+    assert_eq!(
+        ambiguous_forms("/:a(/:b)?(/:c)?/.."),
+        Some((String::from("/:a/:b/:c/.."), String::from("/:a/:c/..")))
+    );
+    assert_eq!(ambiguous_forms("/archive(/year/:)?(/month/:)?"), None);
+    assert_eq!(
+        ambiguous_forms("/tag/:tag(/page/:page)?/.."),
+        Some((
+            String::from("/tag/:tag/page/:page/.."),
+            String::from("/tag/:tag/..")
+        ))
+    );
+    assert_eq!(
+        ambiguous_forms("/files(/:)?/*"),
+        Some((String::from("/files/:/*"), String::from("/files/*")))
+    );
+    assert_eq!(ambiguous_forms("/tag/:tag(/page/:page)?/posts/.."), None);
+    assert_eq!(ambiguous_forms(r"/files(/:<\d+>)?/list/*"), None);
+}
+
+#[test]
+fn test_path_parsing_constraints() {
+    assert_eq!(
+        Path::parse_str(r"/post/:id<\d+>/:<[a-z-]+>/"),
+        Ok(Path {
+            components: vec![
+                PathComponent::Static(String::from("post")),
+                PathComponent::Param(Some(String::from("id")), Some(String::from(r"\d+"))),
+                PathComponent::Param(None, Some(String::from("[a-z-]+"))),
+            ],
+            sub_route: None,
+            catch_all: None,
+            trailing_slash: true,
+            span: Span::call_site(),
+        })
+    );
+
+    assert_eq!(
+        Path::parse_str(r"/:<(?P<id>\d+)>"),
+        Ok(Path {
+            components: vec![PathComponent::Param(
+                None,
+                Some(String::from(r"(?P<id>\d+)"))
+            )],
+            sub_route: None,
+            catch_all: None,
             trailing_slash: false,
             span: Span::call_site(),
         })
     );
+
+    assert!(Path::parse_str(r"/post/:id<\d+").is_err());
+    assert!(Path::parse_str(r"/post/:id<a/b>").is_err());
+    assert!(Path::parse_str(r"/post/:id<[0-9>").is_err());
+    assert!(Path::parse_str(r"/post/:id<>").is_err());
+    // The constraint is valid alone, but not once wrapped in the group anchored by the runtime (the comment swallows its end).
+    assert!(Path::parse_str(r"/post/:id<(?x)\d+#x>").is_err());
+    assert!(Path::parse_str(r"/file-:id<(?x)\d+#x>.json").is_err());
+}
+
+#[test]
+fn test_path_parsing_mixed_segments() {
+    assert_eq!(
+        Path::parse_str(r"/files/file-:id<\d+>.json/v:version(/:lang.html)?"),
+        Ok(Path {
+            components: vec![
+                PathComponent::Static(String::from("files")),
+                PathComponent::Mixed(vec![
+                    PathComponent::Static(String::from("file-")),
+                    PathComponent::Param(Some(String::from("id")), Some(String::from(r"\d+"))),
+                    PathComponent::Static(String::from(".json")),
+                ]),
+                PathComponent::Mixed(vec![
+                    PathComponent::Static(String::from("v")),
+                    PathComponent::Param(Some(String::from("version")), None),
+                ]),
+                PathComponent::Optional(vec![PathComponent::Mixed(vec![
+                    PathComponent::Param(Some(String::from("lang")), None),
+                    PathComponent::Static(String::from(".html")),
+                ])]),
+            ],
+            sub_route: None,
+            catch_all: None,
+            trailing_slash: false,
+            span: Span::call_site(),
+        })
+    );
+
+    assert_eq!(
+        Path::parse_str("/:a<[0-9]+>:b/").map(|path| path.params().count()),
+        Ok(2)
+    );
+
+    assert!(Path::parse_str("/file-:").is_ok());
+    assert!(Path::parse_str("/file-:id<[0-9>.json").is_err());
+    assert!(Path::parse_str("/blog(/:year-:month)?").is_err());
+
+    // ":" is a static character when it can't start a param: after static text, and followed by something else than a field, a
+    // constraint or the end of the segment.
+    let components = |path: &str| Path::parse_str(path).map(|path| path.components);
+    assert_eq!(
+        components("/time/12:30/a:-b"),
+        Ok(vec![
+            PathComponent::Static(String::from("time")),
+            PathComponent::Static(String::from("12:30")),
+            PathComponent::Static(String::from("a:-b")),
+        ])
+    );
+    assert_eq!(
+        components("/a:b"),
+        Ok(vec![PathComponent::Mixed(vec![
+            PathComponent::Static(String::from("a")),
+            PathComponent::Param(Some(String::from("b")), None),
+        ])])
+    );
+    assert_eq!(
+        components("/a:"),
+        Ok(vec![PathComponent::Mixed(vec![
+            PathComponent::Static(String::from("a")),
+            PathComponent::Param(None, None),
+        ])])
+    );
+    assert_eq!(
+        components("/:.json"),
+        Ok(vec![PathComponent::Mixed(vec![
+            PathComponent::Param(None, None),
+            PathComponent::Static(String::from(".json")),
+        ])])
+    );
+
+    // Fields are Unicode identifiers.
+    assert_eq!(
+        Path::parse_str("/article/:numéro.html/..sous_route"),
+        Ok(Path {
+            components: vec![
+                PathComponent::Static(String::from("article")),
+                PathComponent::Mixed(vec![
+                    PathComponent::Param(Some(String::from("numéro")), None),
+                    PathComponent::Static(String::from(".html")),
+                ]),
+            ],
+            sub_route: Some(SubRoute::Named(String::from("sous_route"))),
+            catch_all: None,
+            trailing_slash: false,
+            span: Span::call_site(),
+        })
+    );
+}
+
+#[test]
+fn test_path_display() {
+    for path in [
+        "/",
+        "/home",
+        "/home/",
+        "/hello/:",
+        "/hello/:name/:age/aaa/..rest",
+        "/hello/:/..",
+        "/static/*path",
+        "/:/*",
+        r"/blog(/page/:page<\d+>)?(/:)?/",
+        r"/post/:id<\d+>/:<[a-z]+>/",
+        r"/file-:id<\d+>.json/v:/:a:b(/:lang.html)?",
+    ] {
+        assert_eq!(Path::parse_str(path).unwrap().to_string(), path);
+    }
+}
+
+#[test]
+fn test_path_can_match_same_url_as() {
+    fn can_match_same_url(a: &str, b: &str) -> bool {
+        let a = Path::parse_str(a).unwrap();
+        let b = Path::parse_str(b).unwrap();
+        assert_eq!(
+            a.can_match_same_url_as(&b, false).unwrap(),
+            b.can_match_same_url_as(&a, false).unwrap()
+        );
+        a.can_match_same_url_as(&b, false).unwrap()
+    }
+
+    assert!(can_match_same_url("/", "/"));
+    assert!(can_match_same_url("/hello", "/hello"));
+    assert!(can_match_same_url("/hello/:", "/hello/about"));
+    assert!(can_match_same_url("/hello/:name", "/:/about"));
+    assert!(!can_match_same_url("/hello", "/bonjour"));
+    assert!(!can_match_same_url("/hello", "/hello/"));
+    assert!(!can_match_same_url("/hello/:", "/hello/:/:"));
+    assert!(!can_match_same_url("/hello/:/", "/hello/:name/:age"));
+    assert!(can_match_same_url(r"/post/:id<\d+>", "/post/12"));
+    assert!(can_match_same_url(r"/post/:id<\d+>", "/post/:slug<[a-z]+>"));
+    assert!(!can_match_same_url(r"/post/:id<\d+>", "/post/new"));
+    assert!(!can_match_same_url(r"/post/:id<\d+>", "/post/12a"));
+
+    assert!(can_match_same_url("/hello/..", "/hello/about"));
+    assert!(can_match_same_url("/hello/..", "/:/about/:"));
+    assert!(can_match_same_url("/hello/..", "/hello/about/.."));
+    assert!(can_match_same_url("/..", "/hello"));
+    assert!(!can_match_same_url("/hello/..", "/hello"));
+    assert!(!can_match_same_url("/hello/..", "/hello/"));
+    assert!(!can_match_same_url("/hello/..", "/bonjour/about"));
+    assert!(!can_match_same_url("/hello/..", "/bonjour/.."));
+
+    assert!(can_match_same_url("/static/*path", "/static/style.css"));
+    assert!(can_match_same_url("/static/*path", "/:/:/"));
+    assert!(can_match_same_url("/static/*path", "/static/.."));
+    assert!(can_match_same_url("/*", "/hello"));
+    assert!(!can_match_same_url("/static/*path", "/static"));
+    assert!(!can_match_same_url("/static/*path", "/static/"));
+    assert!(!can_match_same_url("/static/*path", "/assets/style.css"));
+
+    assert!(can_match_same_url("/blog(/page/:page)?", "/blog"));
+    assert!(can_match_same_url("/blog(/page/:page)?", "/blog/page/:n"));
+    assert!(can_match_same_url("/blog(/:page)?", "/:/about"));
+    assert!(!can_match_same_url("/blog(/page/:page)?", "/blog/"));
+    assert!(!can_match_same_url("/blog(/page/:page)?", "/blog/:"));
+    assert!(!can_match_same_url("/blog(/page/:page)?", "/blog/page"));
+
+    assert!(can_match_same_url("/file-:id.json", "/file-12.json"));
+    assert!(can_match_same_url("/file-:id.json", "/:"));
+    assert!(can_match_same_url("/file-:id.json", "/:id.json"));
+    assert!(can_match_same_url(r"/v:version<\d+>", "/v2"));
+    assert!(!can_match_same_url("/file-:id.json", "/file-.json"));
+    assert!(!can_match_same_url("/file-:id.json", "/file-12.xml"));
+    assert!(!can_match_same_url(r"/v:version<\d+>", "/vendor"));
+}
+
+#[test]
+fn test_path_can_match_same_url_as_case_insensitive() {
+    fn can_match_same_url(a: &str, b: &str) -> bool {
+        let a = Path::parse_str(a).unwrap();
+        let b = Path::parse_str(b).unwrap();
+        a.can_match_same_url_as(&b, true).unwrap()
+    }
+
+    assert!(can_match_same_url("/about", "/About"));
+    assert!(can_match_same_url("/a-propos/:", "/A-PROPOS/team"));
+    assert!(!can_match_same_url("/about", "/abouts"));
 }
@@ -0,0 +1,192 @@
+use super::router::*;
+use super::{option_str, sub_route_member, trailing_slash};
+use crate::helpers;
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{Field, Ident, Member};
+
+pub fn routes_const(
+    routes: &[Route],
+    options: &RouterOptions,
+    chemin_crate: &TokenStream,
+) -> TokenStream {
+    let route_infos = routes
+        .iter()
+        .map(|route| route_info(route, options, chemin_crate));
+
+    quote!(
+        const ROUTES: &'static [#chemin_crate::RouteInfo] = &[#(#route_infos),*];
+    )
+}
+
+fn route_info(route: &Route, options: &RouterOptions, chemin_crate: &TokenStream) -> TokenStream {
+    let variant_name = route.variant.ident.to_string();
+
+    let path_infos = route.localized_routes.iter().map(|localized_route| {
+        let pattern = localized_route.path.to_string();
+        let mut locales = localized_route.locales.iter().collect::<Vec<_>>();
+        locales.sort();
+        quote!(#chemin_crate::PathInfo {
+            pattern: #pattern,
+            locales: &[#(#locales),*],
+        })
+    });
+
+    // All the localized routes of a variant have the same sub-route field.
+    let sub_route_member = route
+        .localized_routes
+        .first()
+        .and_then(|localized_route| sub_route_member(route, localized_route));
+
+    let mut param_infos = Vec::new();
+    let mut sub_route_info = quote!(::std::option::Option::None);
+
+    for (i, field) in route.variant.fields.iter().enumerate() {
+        let name = field_name(field, i);
+        let ty = &field.ty;
+        let ty_name = helpers::tokens_to_string(quote!(#ty));
+
+        let is_sub_route = match &sub_route_member {
+            Some(Member::Named(ident)) => field.ident.as_ref() == Some(ident),
+            Some(Member::Unnamed(index)) => index.index as usize == i,
+            None => false,
+        };
+        let is_query_param = field
+            .attrs
+            .iter()
+            .any(|attr| attr.path.is_ident("query_param") || attr.path.is_ident("query_rest"));
+
+        if is_sub_route {
+            let query_prefix = option_str(route.query_prefix.as_deref());
+            sub_route_info = quote!(::std::option::Option::Some(#chemin_crate::SubRouteInfo {
+                name: #name,
+                ty: #ty_name,
+                routes: <#ty as #chemin_crate::Chemin>::ROUTES,
+                query_prefix: #query_prefix,
+            }));
+        } else if !is_query_param {
+            param_infos.push(quote!(#chemin_crate::ParamInfo {
+                name: #name,
+                ty: #ty_name,
+            }));
+        }
+    }
+
+    let query_param_infos = route.query_params.iter().map(|query_param| {
+        let field_ident = &query_param.field_ident;
+        let name = field_ident.to_string();
+        let key = &query_param.key;
+        let aliases = &query_param.aliases;
+        let field = route
+            .variant
+            .fields
+            .iter()
+            .find(|field| field.ident.as_ref() == Some(field_ident))
+            .unwrap();
+        let ty = &field.ty;
+        let ty_name = helpers::tokens_to_string(quote!(#ty));
+
+        let kind = match &query_param.kind {
+            QueryParamKind::Mandatory => quote!(Mandatory),
+            QueryParamKind::Optional => quote!(Optional),
+            QueryParamKind::WithDefaultValue(default_value) => {
+                let default_value = helpers::tokens_to_string(quote!(#default_value));
+                quote!(WithDefaultValue(#default_value))
+            }
+            QueryParamKind::Multiple(separator) => {
+                let separator = option_str(separator.as_deref());
+                quote!(Multiple(#separator))
+            }
+            QueryParamKind::Flatten => quote!(Flatten),
+            QueryParamKind::Flag => quote!(Flag),
+        };
+
+        quote!(#chemin_crate::QueryParamInfo {
+            name: #name,
+            key: #key,
+            aliases: &[#(#aliases),*],
+            ty: #ty_name,
+            kind: #chemin_crate::QueryParamKind::#kind,
+        })
+    });
+
+    let query_rest_name = route.query_rest.as_ref().map(Ident::to_string);
+    let query_rest_info = option_str(query_rest_name.as_deref());
+    let trailing_slash = trailing_slash(options.trailing_slash, chemin_crate);
+
+    quote!(#chemin_crate::RouteInfo {
+        variant: #variant_name,
+        paths: &[#(#path_infos),*],
+        params: &[#(#param_infos),*],
+        query_params: &[#(#query_param_infos),*],
+        query_rest: #query_rest_info,
+        sub_route: #sub_route_info,
+        trailing_slash: #trailing_slash,
+    })
+}
+
+/// Constant assertions checking that the query string parameters of a variant don't use the same keys as those of its sub-route (or
+/// of their own sub-routes), since they are read from the same query string. With a query prefix, only the keys starting with it can
+/// be used by the sub-route. They can't be generated for generic enums.
+pub fn query_key_checks(
+    enum_ident: &Ident,
+    routes: &[Route],
+    chemin_crate: &TokenStream,
+) -> TokenStream {
+    let mut checks = Vec::new();
+
+    for route in routes {
+        // Query string parameters are only supported for named fields, so the sub-route is named too.
+        let sub_route_field = match route
+            .localized_routes
+            .first()
+            .and_then(|localized_route| sub_route_member(route, localized_route))
+        {
+            Some(Member::Named(ident)) => route
+                .variant
+                .fields
+                .iter()
+                .find(|field| field.ident.as_ref() == Some(&ident)),
+            _ => None,
+        };
+
+        let ty = match sub_route_field {
+            Some(field) => &field.ty,
+            None => continue,
+        };
+        let ty_name = helpers::tokens_to_string(quote!(#ty));
+
+        for query_param in &route.query_params {
+            for key in query_param.keys() {
+                let sub_route_key = match &route.query_prefix {
+                    Some(query_prefix) => match key.strip_prefix(query_prefix.as_str()) {
+                        Some(sub_route_key) => sub_route_key,
+                        None => continue,
+                    },
+                    None => key.as_str(),
+                };
+                let message = format!(
+                    "The query string parameter key \"{}\" of `{}::{}` is also used in its sub-route `{}`",
+                    key, enum_ident, route.variant.ident, ty_name,
+                );
+
+                checks.push(quote_spanned!(query_param.field_ident.span()=>
+                    const _: () = ::std::assert!(
+                        !#chemin_crate::route_tree_uses_query_key(<#ty as #chemin_crate::Chemin>::ROUTES, #sub_route_key),
+                        "{}",
+                        #message,
+                    );
+                ));
+            }
+        }
+    }
+
+    quote!(#(#checks)*)
+}
+
+fn field_name(field: &Field, i: usize) -> String {
+    match &field.ident {
+        Some(ident) => ident.to_string(),
+        None => i.to_string(),
+    }
+}
@@ -0,0 +1,154 @@
+use super::route_sub_route_member;
+use super::router::*;
+use proc_macro2::TokenStream;
+use quote::quote;
+
+pub fn unknown_query_items(
+    routes: &[Route],
+    options: &RouterOptions,
+    chemin_crate: &TokenStream,
+) -> TokenStream {
+    let unknown_query = match options.unknown_query {
+        UnknownQuery::Ignore => quote!(#chemin_crate::UnknownQuery::Ignore),
+        UnknownQuery::Reject => quote!(#chemin_crate::UnknownQuery::Reject),
+        UnknownQuery::Capture => quote!(#chemin_crate::UnknownQuery::Capture),
+    };
+    let claims_query_key_method = claims_query_key_method(routes, chemin_crate);
+    let set_query_rest_method = set_query_rest_method(routes, chemin_crate);
+
+    quote!(
+        const UNKNOWN_QUERY: #chemin_crate::UnknownQuery = #unknown_query;
+        #claims_query_key_method
+        #set_query_rest_method
+    )
+}
+
+fn claims_query_key_method(routes: &[Route], chemin_crate: &TokenStream) -> TokenStream {
+    let mut key_is_used = false;
+
+    let match_arms = routes
+        .iter()
+        .map(|route| {
+            let variant_ident = &route.variant.ident;
+            let keys = route
+                .query_params
+                .iter()
+                .flat_map(QueryParam::keys)
+                .collect::<Vec<_>>();
+
+            // The keys of a flattened query param are only known at runtime, from the fields of its type.
+            let flattened_fields = route
+                .query_params
+                .iter()
+                .filter(|query_param| query_param.kind == QueryParamKind::Flatten)
+                .map(|query_param| &query_param.field_ident)
+                .collect::<Vec<_>>();
+
+            let mut claims = Vec::new();
+
+            if !keys.is_empty() {
+                claims.push(quote!(::std::matches!(__chemin_key, #(#keys)|*)));
+            }
+
+            claims.extend(flattened_fields.iter().map(|field_ident| {
+                quote!(#chemin_crate::flattened_query_params_claim_key(#field_ident, __chemin_key))
+            }));
+
+            let claimed_by_variant = if claims.is_empty() {
+                quote!(false)
+            } else {
+                key_is_used = true;
+                quote!(#(#claims)||*)
+            };
+
+            match route_sub_route_member(route) {
+                Some(member) => {
+                    key_is_used = true;
+                    let claimed_by_sub_route = match &route.query_prefix {
+                        Some(query_prefix) => quote!(
+                            __chemin_key.strip_prefix(#query_prefix).is_some_and(|__chemin_key| {
+                                #chemin_crate::Chemin::claims_query_key(__chemin_sub_route, __chemin_key)
+                            })
+                        ),
+                        None => quote!(#chemin_crate::Chemin::claims_query_key(__chemin_sub_route, __chemin_key)),
+                    };
+                    quote!(Self::#variant_ident { #member: __chemin_sub_route, #(#flattened_fields,)* .. } => {
+                        #claimed_by_variant || #claimed_by_sub_route
+                    })
+                }
+
+                None => quote!(Self::#variant_ident { #(#flattened_fields,)* .. } => #claimed_by_variant),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let key_param = if key_is_used {
+        quote!(__chemin_key)
+    } else {
+        quote!(_)
+    };
+
+    quote!(
+        fn claims_query_key(&self, #key_param: &::std::primitive::str) -> ::std::primitive::bool {
+            match self {
+                #(#match_arms,)*
+            }
+        }
+    )
+}
+
+fn set_query_rest_method(routes: &[Route], chemin_crate: &TokenStream) -> TokenStream {
+    let mut query_rest_is_used = false;
+
+    let match_arms = routes
+        .iter()
+        .map(|route| {
+            let variant_ident = &route.variant.ident;
+
+            // The outermost query rest of the route tree collects the unknown query string parameters.
+            match (&route.query_rest, route_sub_route_member(route)) {
+                (Some(field_ident), _) => {
+                    query_rest_is_used = true;
+                    quote!(Self::#variant_ident { #field_ident, .. } => *#field_ident = __chemin_query_rest)
+                }
+
+                (None, Some(member)) => {
+                    query_rest_is_used = true;
+                    // With a query prefix, only the query string parameters whose keys start with it are given to the sub-route,
+                    // since they are generated again with the prefix. The other ones are ignored, as without a query rest.
+                    let query_rest = match &route.query_prefix {
+                        Some(query_prefix) => quote!(#chemin_crate::strip_query_prefix_from_pairs(
+                            __chemin_query_rest,
+                            #query_prefix,
+                        )),
+                        None => quote!(__chemin_query_rest),
+                    };
+                    quote!(Self::#variant_ident { #member: __chemin_sub_route, .. } => {
+                        #chemin_crate::Chemin::set_query_rest(__chemin_sub_route, #query_rest)
+                    })
+                }
+
+                (None, None) => quote!(Self::#variant_ident { .. } => {}),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    if !query_rest_is_used {
+        // The default implementation does nothing.
+        return TokenStream::new();
+    }
+
+    quote!(
+        fn set_query_rest(
+            &mut self,
+            __chemin_query_rest: ::std::vec::Vec<(
+                ::std::string::String,
+                ::std::option::Option<::std::string::String>,
+            )>,
+        ) {
+            match self {
+                #(#match_arms,)*
+            }
+        }
+    )
+}
@@ -1,3 +1,4 @@
+use proc_macro2::{Delimiter, TokenStream, TokenTree};
 use syn::parse::ParseBuffer;
 use syn::{Error, Result};
 
@@ -8,3 +9,62 @@ pub fn parse_eos(input: &ParseBuffer) -> Result<()> {
         Err(Error::new(input.span(), "Syntax Error: unexpected token"))
     }
 }
+
+/// Converts tokens to a string looking like hand-written code (`Option<u8>` instead of `Option < u8 >`).
+pub fn tokens_to_string(tokens: TokenStream) -> String {
+    fn push_tokens(string: &mut String, tokens: TokenStream) {
+        for token in tokens {
+            match token {
+                TokenTree::Group(group) => {
+                    let (open, close) = match group.delimiter() {
+                        Delimiter::Parenthesis => ("(", ")"),
+                        Delimiter::Brace => ("{", "}"),
+                        Delimiter::Bracket => ("[", "]"),
+                        Delimiter::None => ("", ""),
+                    };
+
+                    string.push_str(open);
+                    push_tokens(string, group.stream());
+                    string.push_str(close);
+                }
+
+                TokenTree::Ident(_) | TokenTree::Literal(_) => {
+                    if string.ends_with(|c: char| c.is_alphanumeric() || c == '_' || c == '"') {
+                        string.push(' ');
+                    }
+
+                    string.push_str(&token.to_string());
+                }
+
+                TokenTree::Punct(punct) => {
+                    string.push(punct.as_char());
+
+                    if punct.as_char() == ',' {
+                        string.push(' ');
+                    }
+                }
+            }
+        }
+    }
+
+    let mut string = String::new();
+    push_tokens(&mut string, tokens);
+    string
+}
+
+#[test]
+fn test_tokens_to_string() {
+    use quote::quote;
+
+    assert_eq!(tokens_to_string(quote!(u8)), "u8");
+    assert_eq!(tokens_to_string(quote!(Option<u8>)), "Option<u8>");
+    assert_eq!(
+        tokens_to_string(quote!(std::collections::HashMap<String, Vec<u8> >)),
+        "std::collections::HashMap<String, Vec<u8>>"
+    );
+    assert_eq!(tokens_to_string(quote!(&'static str)), "&'static str");
+    assert_eq!(
+        tokens_to_string(quote!(String::from("default"))),
+        "String::from(\"default\")"
+    );
+}
@@ -20,7 +20,7 @@ fn chemin_crate() -> proc_macro2::TokenStream {
     }
 }
 
-#[proc_macro_derive(Chemin, attributes(route, query_param))]
+#[proc_macro_derive(Chemin, attributes(chemin, route, query_param, query_rest, param))]
 pub fn derive_chemin(item: TokenStream) -> TokenStream {
     derive_chemin::derive_chemin(item.into(), &chemin_crate()).into()
 }
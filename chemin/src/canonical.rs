@@ -0,0 +1,37 @@
+use std::borrow::Cow;
+
+/// The canonical form of a url, obtained with [Chemin::canonicalize](crate::Chemin::canonicalize).
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+pub struct Canonical {
+    /// The canonical url.
+    pub url: String,
+    /// Whether the canonical url differs from the original one, in which case the client should be redirected to it.
+    pub changed: bool,
+}
+
+/// Replaces the consecutive slashes of `path` by a single one.
+pub(crate) fn remove_duplicated_slashes(path: &str) -> Cow<'_, str> {
+    if path.contains("//") {
+        let mut result = String::with_capacity(path.len());
+
+        for c in path.chars() {
+            if c != '/' || !result.ends_with('/') {
+                result.push(c);
+            }
+        }
+
+        Cow::Owned(result)
+    } else {
+        Cow::Borrowed(path)
+    }
+}
+
+#[test]
+fn test_remove_duplicated_slashes() {
+    assert_eq!(remove_duplicated_slashes("/hello/john"), "/hello/john");
+    assert_eq!(
+        remove_duplicated_slashes("//hello///john//"),
+        "/hello/john/"
+    );
+    assert_eq!(remove_duplicated_slashes("//"), "/");
+}
@@ -0,0 +1,160 @@
+use crate::{decode_param, encode_param, FieldErrorKind};
+use std::borrow::Cow;
+use std::path::{self, Component, PathBuf};
+
+/// A type which can be used for a catch-all url parameter (as in `"/static/*path"`), which captures the rest of the url.
+///
+/// It is implemented for [String] (the segments joined with `/`), [PathBuf] and `Vec<String>` (the segments themselves).
+pub trait CatchAllParam: Sized {
+    /// Builds the value from the segments of the rest of the url, which are percent-decoded if the url parameters are decoded.
+    /// Returns an error message if the segments are invalid.
+    fn from_segments(segments: Vec<String>) -> Result<Self, String>;
+
+    /// Returns the segments of the rest of the url. They are percent-encoded afterwards if the url parameters are encoded.
+    fn to_segments(&self) -> Vec<String>;
+}
+
+impl CatchAllParam for String {
+    fn from_segments(segments: Vec<String>) -> Result<Self, String> {
+        Ok(segments.join("/"))
+    }
+
+    fn to_segments(&self) -> Vec<String> {
+        self.split('/').map(ToOwned::to_owned).collect()
+    }
+}
+
+impl CatchAllParam for Vec<String> {
+    fn from_segments(segments: Vec<String>) -> Result<Self, String> {
+        Ok(segments)
+    }
+
+    fn to_segments(&self) -> Vec<String> {
+        self.clone()
+    }
+}
+
+/// The path can't go up the directory tree: the `..` segments, and the segments containing a path separator once percent-decoded,
+/// are rejected. Only the normal components of the path are generated (the root, `.` and `..` components are skipped).
+impl CatchAllParam for PathBuf {
+    fn from_segments(segments: Vec<String>) -> Result<Self, String> {
+        for segment in &segments {
+            if segment == ".." {
+                return Err(String::from("the path contains a `..` segment"));
+            }
+
+            if segment.chars().any(path::is_separator) {
+                return Err(String::from(
+                    "a segment of the path contains a path separator",
+                ));
+            }
+        }
+
+        Ok(segments.into_iter().collect())
+    }
+
+    fn to_segments(&self) -> Vec<String> {
+        self.components()
+            .filter_map(|component| match component {
+                Component::Normal(segment) => Some(segment.to_string_lossy().into_owned()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+#[doc(hidden)]
+pub fn parse_catch_all<T: CatchAllParam>(
+    rest: &str,
+    decode_params: bool,
+) -> Result<T, FieldErrorKind> {
+    let segments = rest
+        .split('/')
+        .map(|segment| {
+            if decode_params {
+                decode_param(segment)
+                    .map(Cow::into_owned)
+                    .ok_or(FieldErrorKind::InvalidEncoding)
+            } else {
+                Ok(segment.to_owned())
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    T::from_segments(segments).map_err(FieldErrorKind::InvalidParam)
+}
+
+/// Returns [None] if the rest of the url would be empty, since it wouldn't be parsed back as a catch-all param.
+#[doc(hidden)]
+pub fn format_catch_all(value: &impl CatchAllParam, encode_params: bool) -> Option<String> {
+    let segments = value.to_segments();
+
+    let rest = if encode_params {
+        segments
+            .iter()
+            .map(encode_param)
+            .collect::<Vec<_>>()
+            .join("/")
+    } else {
+        segments.join("/")
+    };
+
+    (!rest.is_empty()).then_some(rest)
+}
+
+#[test]
+fn test_catch_all_params() {
+    assert_eq!(
+        parse_catch_all::<String>("css/main%20style.css", true),
+        Ok(String::from("css/main style.css"))
+    );
+    assert_eq!(
+        parse_catch_all::<String>("css/main%20style.css", false),
+        Ok(String::from("css/main%20style.css"))
+    );
+    assert_eq!(
+        parse_catch_all::<Vec<String>>("a%2Fb/c/", true),
+        Ok(vec![String::from("a/b"), String::from("c"), String::new()])
+    );
+    assert_eq!(
+        parse_catch_all::<String>("a/%FF", true),
+        Err(FieldErrorKind::InvalidEncoding)
+    );
+
+    assert_eq!(
+        parse_catch_all::<PathBuf>("css/style.css", true),
+        Ok(PathBuf::from("css/style.css"))
+    );
+    assert_eq!(
+        parse_catch_all::<PathBuf>("css/../../etc/passwd", true),
+        Err(FieldErrorKind::InvalidParam(String::from(
+            "the path contains a `..` segment"
+        )))
+    );
+    assert_eq!(
+        parse_catch_all::<PathBuf>("css/..%2F..%2Fetc", true),
+        Err(FieldErrorKind::InvalidParam(String::from(
+            "a segment of the path contains a path separator"
+        )))
+    );
+
+    assert_eq!(
+        format_catch_all(&String::from("css/main style.css"), true).as_deref(),
+        Some("css/main%20style.css")
+    );
+    assert_eq!(
+        format_catch_all(&String::from("css/main style.css"), false).as_deref(),
+        Some("css/main style.css")
+    );
+    assert_eq!(
+        format_catch_all(&vec![String::from("a/b"), String::from("c")], true).as_deref(),
+        Some("a%2Fb/c")
+    );
+    assert_eq!(
+        format_catch_all(&PathBuf::from("css/./style.css"), true).as_deref(),
+        Some("css/style.css")
+    );
+    assert_eq!(format_catch_all(&String::new(), true), None);
+    assert_eq!(format_catch_all(&Vec::<String>::new(), true), None);
+    assert_eq!(format_catch_all(&PathBuf::from("/"), true), None);
+}
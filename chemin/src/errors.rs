@@ -0,0 +1,484 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// Identifies a variant of an enum deriving [Chemin](crate::Chemin).
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct RouteVariant {
+    /// The name of the enum.
+    pub enum_name: &'static str,
+    /// The name of the variant.
+    pub variant: &'static str,
+}
+
+impl Display for RouteVariant {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}::{}", self.enum_name, self.variant)
+    }
+}
+
+/// The error returned by [Chemin::try_parse](crate::Chemin::try_parse).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseError {
+    /// The url doesn't correspond to any route.
+    NotFound,
+
+    /// The url corresponds to a route, but one of its fields couldn't be parsed.
+    InvalidField {
+        /// The chain of variants leading to the field, from the outermost one to the one containing the field. There is more than one
+        /// variant when the field is defined in a sub-route.
+        variants: Vec<RouteVariant>,
+        /// The name of the field. For unnamed fields, it is the index of the field (`"0"`, `"1"`, ...).
+        field: &'static str,
+        /// Why the field couldn't be parsed.
+        kind: FieldErrorKind,
+    },
+
+    /// The url corresponds to a route whose trailing slash policy is [TrailingSlash::Redirect](crate::TrailingSlash::Redirect), but
+    /// its trailing slash doesn't match the path of the route. See [Chemin::parse_or_redirect](crate::Chemin::parse_or_redirect).
+    TrailingSlashMismatch,
+
+    /// The url contains a query string parameter which isn't claimed by any field of the route, and the policy of the route is
+    /// [UnknownQuery::Reject](crate::UnknownQuery::Reject). Contains the key of the query string parameter.
+    UnknownQueryParam(String),
+}
+
+/// Why a field couldn't be parsed. See [ParseError::InvalidField].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum FieldErrorKind {
+    /// The percent-decoding of the url parameter produced invalid UTF-8.
+    InvalidEncoding,
+    /// The url parameter failed to parse. Contains the error message of the parsing function.
+    InvalidParam(String),
+    /// A mandatory query string parameter is missing.
+    MissingQueryParam,
+    /// The query string parameter failed to parse. Contains the error message of the parsing function.
+    InvalidQueryParam(String),
+}
+
+impl ParseError {
+    #[doc(hidden)]
+    pub fn invalid_field(variant: RouteVariant, field: &'static str, kind: FieldErrorKind) -> Self {
+        Self::InvalidField {
+            variants: vec![variant],
+            field,
+            kind,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn in_parent_variant(mut self, parent: RouteVariant) -> Self {
+        if let Self::InvalidField { variants, .. } = &mut self {
+            variants.insert(0, parent);
+        }
+
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::NotFound => write!(f, "no route corresponds to this url"),
+
+            Self::TrailingSlashMismatch => write!(
+                f,
+                "the trailing slash of this url doesn't match the path of its route"
+            ),
+
+            Self::UnknownQueryParam(key) => write!(f, "unknown query string parameter `{}`", key),
+
+            Self::InvalidField {
+                variants,
+                field,
+                kind,
+            } => {
+                write!(f, "invalid field `{}` in route ", field)?;
+                write_variants(f, variants)?;
+                write!(f, ": {}", kind)
+            }
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+impl Display for FieldErrorKind {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidEncoding => {
+                write!(f, "the url parameter is not valid percent-encoded UTF-8")
+            }
+            Self::InvalidParam(message) => write!(f, "invalid url parameter ({})", message),
+            Self::MissingQueryParam => write!(f, "missing query string parameter"),
+            Self::InvalidQueryParam(message) => {
+                write!(f, "invalid query string parameter ({})", message)
+            }
+        }
+    }
+}
+
+/// The error returned by [Chemin::try_generate_url](crate::Chemin::try_generate_url).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum GenerateError {
+    /// A route isn't defined for the requested locale.
+    MissingLocale {
+        /// The chain of variants leading to the route which isn't defined for the requested locale, from the outermost one to the
+        /// one lacking the locale. There is more than one variant when the locale is missing in a sub-route.
+        variants: Vec<RouteVariant>,
+        /// The requested locale, or [None] if no locale was provided although the route is specific to some locales.
+        locale: Option<String>,
+    },
+
+    /// A url parameter doesn't match the constraint defined in the path of the route (as in `"/post/:id<\\d+>"`), so the
+    /// generated url wouldn't correspond to this route.
+    InvalidParam {
+        /// The chain of variants leading to the field, from the outermost one to the one containing the field. There is more than one
+        /// variant when the field is defined in a sub-route.
+        variants: Vec<RouteVariant>,
+        /// The name of the field. For unnamed fields, it is the index of the field (`"0"`, `"1"`, ...).
+        field: &'static str,
+        /// The constraint, as written in the path of the route.
+        constraint: &'static str,
+    },
+
+    /// A url parameter is empty although it can't be (as a catch-all parameter, as in `"/static/*path"`, or a url parameter without
+    /// constraint of a segment mixing static text and url parameters, as in `"/file-:id.json"`), so the generated url wouldn't
+    /// correspond to this route.
+    EmptyParam {
+        /// The chain of variants leading to the field, from the outermost one to the one containing the field. There is more than one
+        /// variant when the field is defined in a sub-route.
+        variants: Vec<RouteVariant>,
+        /// The name of the field. For unnamed fields, it is the index of the field (`"0"`, `"1"`, ...).
+        field: &'static str,
+    },
+
+    /// The value of a field declared with `#[query_param(flatten)]` couldn't be serialized into query string parameters (for example
+    /// because it isn't a struct or a map).
+    InvalidQueryParams {
+        /// The chain of variants leading to the field, from the outermost one to the one containing the field. There is more than one
+        /// variant when the field is defined in a sub-route.
+        variants: Vec<RouteVariant>,
+        /// The name of the field.
+        field: &'static str,
+        /// The error message of the serializer.
+        message: String,
+    },
+
+    /// A value of a field declared with `#[query_param(multiple, separator = "...")]` contains the separator, so it would be split
+    /// into several values when parsing the generated url.
+    SeparatorInQueryParam {
+        /// The chain of variants leading to the field, from the outermost one to the one containing the field. There is more than one
+        /// variant when the field is defined in a sub-route.
+        variants: Vec<RouteVariant>,
+        /// The name of the field.
+        field: &'static str,
+        /// The separator, as written in the attribute of the field.
+        separator: &'static str,
+        /// The formatted value containing the separator.
+        value: String,
+    },
+}
+
+impl GenerateError {
+    #[doc(hidden)]
+    pub fn missing_locale(variant: RouteVariant, locale: Option<&str>) -> Self {
+        Self::MissingLocale {
+            variants: vec![variant],
+            locale: locale.map(ToOwned::to_owned),
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn invalid_param(
+        variant: RouteVariant,
+        field: &'static str,
+        constraint: &'static str,
+    ) -> Self {
+        Self::InvalidParam {
+            variants: vec![variant],
+            field,
+            constraint,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn empty_param(variant: RouteVariant, field: &'static str) -> Self {
+        Self::EmptyParam {
+            variants: vec![variant],
+            field,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn invalid_query_params(
+        variant: RouteVariant,
+        field: &'static str,
+        message: String,
+    ) -> Self {
+        Self::InvalidQueryParams {
+            variants: vec![variant],
+            field,
+            message,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn separator_in_query_param(
+        variant: RouteVariant,
+        field: &'static str,
+        separator: &'static str,
+        value: String,
+    ) -> Self {
+        Self::SeparatorInQueryParam {
+            variants: vec![variant],
+            field,
+            separator,
+            value,
+        }
+    }
+
+    #[doc(hidden)]
+    pub fn in_parent_variant(mut self, parent: RouteVariant) -> Self {
+        match &mut self {
+            Self::MissingLocale { variants, .. }
+            | Self::InvalidParam { variants, .. }
+            | Self::EmptyParam { variants, .. }
+            | Self::InvalidQueryParams { variants, .. }
+            | Self::SeparatorInQueryParam { variants, .. } => variants.insert(0, parent),
+        }
+
+        self
+    }
+}
+
+impl Display for GenerateError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::MissingLocale { variants, locale } => {
+                write!(f, "route ")?;
+                write_variants(f, variants)?;
+
+                match locale {
+                    Some(locale) => write!(f, " is not defined for locale `{}`", locale),
+                    None => write!(
+                        f,
+                        " is specific to some locales, but no locale was provided"
+                    ),
+                }
+            }
+
+            Self::InvalidParam {
+                variants,
+                field,
+                constraint,
+            } => {
+                write!(f, "invalid field `{}` in route ", field)?;
+                write_variants(f, variants)?;
+                write!(
+                    f,
+                    ": the url parameter doesn't match the constraint `{}`",
+                    constraint
+                )
+            }
+
+            Self::EmptyParam { variants, field } => {
+                write!(f, "invalid field `{}` in route ", field)?;
+                write_variants(f, variants)?;
+                write!(f, ": the url parameter is empty")
+            }
+
+            Self::InvalidQueryParams {
+                variants,
+                field,
+                message,
+            } => {
+                write!(f, "invalid field `{}` in route ", field)?;
+                write_variants(f, variants)?;
+                write!(
+                    f,
+                    ": the query string parameters couldn't be serialized ({})",
+                    message
+                )
+            }
+
+            Self::SeparatorInQueryParam {
+                variants,
+                field,
+                separator,
+                value,
+            } => {
+                write!(f, "invalid field `{}` in route ", field)?;
+                write_variants(f, variants)?;
+                write!(
+                    f,
+                    ": the value `{}` contains the separator `{}` of the query string parameter",
+                    value, separator
+                )
+            }
+        }
+    }
+}
+
+impl Error for GenerateError {}
+
+fn write_variants(f: &mut Formatter, variants: &[RouteVariant]) -> fmt::Result {
+    for (i, variant) in variants.iter().enumerate() {
+        if i != 0 {
+            write!(f, " > ")?;
+        }
+
+        write!(f, "`{}`", variant)?;
+    }
+
+    Ok(())
+}
+
+#[doc(hidden)]
+pub struct ErrorMessage<'a, E>(pub &'a E);
+
+// Autoref specialization: the generated code calls `(&ErrorMessage(&error)).error_message()` with both traits in scope, which picks
+// `DisplayErrorMessage` when the error type implements Display, and falls back to `GenericErrorMessage` otherwise.
+#[doc(hidden)]
+pub trait DisplayErrorMessage {
+    fn error_message(&self) -> String;
+}
+
+impl<E: Display> DisplayErrorMessage for ErrorMessage<'_, E> {
+    fn error_message(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+#[doc(hidden)]
+pub trait GenericErrorMessage {
+    fn error_message(&self) -> String;
+}
+
+impl<E> GenericErrorMessage for &ErrorMessage<'_, E> {
+    fn error_message(&self) -> String {
+        String::from("invalid value")
+    }
+}
+
+#[test]
+fn test_parse_error_display() {
+    let error = ParseError::invalid_field(
+        RouteVariant {
+            enum_name: "SubRoute",
+            variant: "WithParams",
+        },
+        "age",
+        FieldErrorKind::InvalidQueryParam(String::from("invalid digit found in string")),
+    )
+    .in_parent_variant(RouteVariant {
+        enum_name: "Route",
+        variant: "WithSubRoute",
+    });
+
+    assert_eq!(
+        error.to_string(),
+        "invalid field `age` in route `Route::WithSubRoute` > `SubRoute::WithParams`: invalid query string parameter (invalid digit found in string)",
+    );
+
+    assert_eq!(
+        ParseError::NotFound.in_parent_variant(RouteVariant {
+            enum_name: "Route",
+            variant: "WithSubRoute",
+        }),
+        ParseError::NotFound,
+    );
+
+    assert_eq!(
+        ParseError::UnknownQueryParam(String::from("utm_source")).to_string(),
+        "unknown query string parameter `utm_source`",
+    );
+}
+
+#[test]
+fn test_generate_error_display() {
+    let error = GenerateError::missing_locale(
+        RouteVariant {
+            enum_name: "SubRoute",
+            variant: "Hello",
+        },
+        Some("de"),
+    )
+    .in_parent_variant(RouteVariant {
+        enum_name: "Route",
+        variant: "WithSubRoute",
+    });
+
+    assert_eq!(
+        error.to_string(),
+        "route `Route::WithSubRoute` > `SubRoute::Hello` is not defined for locale `de`",
+    );
+
+    assert_eq!(
+        GenerateError::missing_locale(
+            RouteVariant {
+                enum_name: "Route",
+                variant: "About",
+            },
+            None,
+        )
+        .to_string(),
+        "route `Route::About` is specific to some locales, but no locale was provided",
+    );
+
+    assert_eq!(
+        GenerateError::invalid_param(
+            RouteVariant {
+                enum_name: "SubRoute",
+                variant: "Post",
+            },
+            "id",
+            "\\d+",
+        )
+        .in_parent_variant(RouteVariant {
+            enum_name: "Route",
+            variant: "WithSubRoute",
+        })
+        .to_string(),
+        "invalid field `id` in route `Route::WithSubRoute` > `SubRoute::Post`: the url parameter doesn't match the constraint `\\d+`",
+    );
+
+    assert_eq!(
+        GenerateError::empty_param(
+            RouteVariant {
+                enum_name: "Route",
+                variant: "Static",
+            },
+            "path",
+        )
+        .to_string(),
+        "invalid field `path` in route `Route::Static`: the url parameter is empty",
+    );
+
+    assert_eq!(
+        GenerateError::invalid_query_params(
+            RouteVariant {
+                enum_name: "Route",
+                variant: "Search",
+            },
+            "filter",
+            String::from("top-level serializer supports only maps and structs"),
+        )
+        .to_string(),
+        "invalid field `filter` in route `Route::Search`: the query string parameters couldn't be serialized (top-level serializer \
+        supports only maps and structs)",
+    );
+
+    assert_eq!(
+        GenerateError::separator_in_query_param(
+            RouteVariant {
+                enum_name: "Route",
+                variant: "Posts",
+            },
+            "tags",
+            ",",
+            String::from("a,b"),
+        )
+        .to_string(),
+        "invalid field `tags` in route `Route::Posts`: the value `a,b` contains the separator `,` of the query string parameter",
+    );
+}
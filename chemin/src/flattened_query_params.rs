@@ -0,0 +1,174 @@
+use percent_encoding::{AsciiSet, NON_ALPHANUMERIC};
+use qstring::QString;
+use serde::de::value::Error as DeError;
+use serde::de::{DeserializeOwned, Error as _, Visitor};
+use serde::{Deserializer, Serialize};
+
+// With the `serde` feature, `#[query_param(flatten)]` is supported (see `flattened_query_params_without_serde`).
+#[doc(hidden)]
+#[macro_export]
+macro_rules! require_serde_feature {
+    () => {};
+}
+
+/// The maximum nesting depth of the bracket notation (as in `filter[price][min]=10`).
+const MAX_DEPTH: usize = 5;
+
+/// The characters percent-encoded in the keys given to serde_qs: the brackets are kept, since they are the notation for nesting.
+const KEY_ASCII_SET: &AsciiSet = &NON_ALPHANUMERIC.remove(b'[').remove(b']');
+
+/// Parses the value of a field declared with `#[query_param(flatten)]` from all the query string parameters, with serde_qs. The
+/// parameters which don't correspond to a field of the value are ignored.
+#[doc(hidden)]
+pub fn parse_flattened_query_params<T: DeserializeOwned>(qstring: &QString) -> Result<T, String> {
+    // The pairs of `qstring` are already decoded, so they are encoded again (more strictly than `QString` does, because serde_qs
+    // decodes "+" as a space).
+    let query = qstring
+        .to_pairs()
+        .into_iter()
+        .map(|(key, value)| {
+            format!(
+                "{}={}",
+                percent_encoding::utf8_percent_encode(key, KEY_ASCII_SET),
+                percent_encoding::utf8_percent_encode(value, NON_ALPHANUMERIC),
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+
+    serde_qs::Config::new(MAX_DEPTH, false)
+        .deserialize_str(&query)
+        .map_err(|error| error.to_string())
+}
+
+/// Adds the query string parameters of a field declared with `#[query_param(flatten)]` to `qstring`, serialized with serde_qs.
+#[doc(hidden)]
+pub fn add_flattened_query_params<T: Serialize>(
+    qstring: &mut QString,
+    value: &T,
+) -> Result<(), String> {
+    let query = serde_qs::to_string(value).map_err(|error| error.to_string())?;
+
+    for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        qstring.add_pair((decode(key), decode(value)));
+    }
+
+    Ok(())
+}
+
+/// Returns whether the query string parameter `key` belongs to a field declared with `#[query_param(flatten)]`, whose `value` is only
+/// used to infer its type: the part of the key before the bracket notation must be the name of a field of the type. If the type
+/// doesn't declare its fields (as a map), all the keys are claimed.
+#[doc(hidden)]
+pub fn flattened_query_params_claim_key<T: DeserializeOwned>(_value: &T, key: &str) -> bool {
+    let mut fields = None;
+    let _ = T::deserialize(FieldsRecorder(&mut fields));
+
+    match fields {
+        Some(fields) => {
+            let root = &key[..key.find('[').unwrap_or(key.len())];
+            fields.contains(&root)
+        }
+        None => true,
+    }
+}
+
+/// A deserializer which fails immediately, after recording the names of the fields of the deserialized struct.
+struct FieldsRecorder<'a>(&'a mut Option<&'static [&'static str]>);
+
+impl<'de, 'a> Deserializer<'de> for FieldsRecorder<'a> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, DeError> {
+        Err(DeError::custom("not a struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, DeError> {
+        *self.0 = Some(fields);
+        Err(DeError::custom("fields recorded"))
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option unit unit_struct
+        newtype_struct seq tuple tuple_struct map enum identifier ignored_any
+    }
+}
+
+/// Decodes a key or a value encoded by serde_qs.
+fn decode(value: &str) -> String {
+    percent_encoding::percent_decode_str(&value.replace('+', " "))
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+#[test]
+fn test_flattened_query_params() {
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+    struct Filter {
+        price: Price,
+        tags: Vec<String>,
+        query: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+    struct Price {
+        min: Option<u32>,
+        max: Option<u32>,
+    }
+
+    let filter = Filter {
+        price: Price {
+            min: Some(10),
+            max: None,
+        },
+        tags: vec![String::from("a b"), String::from("c&d")],
+        query: Some(String::from("1+1")),
+    };
+
+    let mut qstring = QString::default();
+    add_flattened_query_params(&mut qstring, &filter).unwrap();
+    assert_eq!(
+        qstring.to_pairs(),
+        vec![
+            ("price[min]", "10"),
+            ("tags[0]", "a b"),
+            ("tags[1]", "c&d"),
+            ("query", "1+1"),
+        ],
+    );
+
+    // The keys are claimed according to the fields of the type, whatever their values.
+    assert!(flattened_query_params_claim_key(&filter, "price[min]"));
+    assert!(flattened_query_params_claim_key(&filter, "tags[]"));
+    assert!(flattened_query_params_claim_key(&filter, "query"));
+    assert!(!flattened_query_params_claim_key(&filter, "page"));
+    assert!(!flattened_query_params_claim_key(&filter, "pri"));
+    let map = std::collections::HashMap::<String, String>::new();
+    assert!(flattened_query_params_claim_key(&map, "page"));
+
+    assert_eq!(parse_flattened_query_params(&qstring), Ok(filter));
+
+    assert_eq!(
+        parse_flattened_query_params(&QString::from(
+            "?price[max]=20&tags[]=a&tags[]=b&page=2&query=a%20b"
+        )),
+        Ok(Filter {
+            price: Price {
+                min: None,
+                max: Some(20),
+            },
+            tags: vec![String::from("a"), String::from("b")],
+            query: Some(String::from("a b")),
+        }),
+    );
+    assert!(parse_flattened_query_params::<Filter>(&QString::from("?price[min]=ten")).is_err());
+    assert!(add_flattened_query_params(&mut QString::default(), &10).is_err());
+}
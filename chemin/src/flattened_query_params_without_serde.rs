@@ -0,0 +1,29 @@
+use qstring::QString;
+
+// Without the `serde` feature, the functions used by `#[query_param(flatten)]` are replaced by these ones, so that the only error
+// is the one of `require_serde_feature!`, and not an unresolved function.
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! require_serde_feature {
+    () => {
+        ::std::compile_error!(
+            "`#[query_param(flatten)]` requires the `serde` feature of chemin (add `features = [\"serde\"]` to the chemin dependency)"
+        )
+    };
+}
+
+#[doc(hidden)]
+pub fn parse_flattened_query_params<T>(_qstring: &QString) -> Result<T, String> {
+    unreachable!("`#[query_param(flatten)]` requires the `serde` feature")
+}
+
+#[doc(hidden)]
+pub fn add_flattened_query_params<T>(_qstring: &mut QString, _value: &T) -> Result<(), String> {
+    unreachable!("`#[query_param(flatten)]` requires the `serde` feature")
+}
+
+#[doc(hidden)]
+pub fn flattened_query_params_claim_key<T>(_value: &T, _key: &str) -> bool {
+    unreachable!("`#[query_param(flatten)]` requires the `serde` feature")
+}
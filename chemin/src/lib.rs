@@ -17,7 +17,7 @@
 //!     Home,
 //!
 //!     /// If there is a trailing slash at the end (example: #[route("/about/")]), it is considered
-//!     /// a different route than without the trailing slash.
+//!     /// a different route than without the trailing slash (unless another policy is chosen, see below).
 //!     ##[route("/about")]
 //!     About,
 //!
@@ -67,6 +67,169 @@
 //! );
 //! ```
 //!
+//! The way trailing slashes are handled can be chosen for a whole enum with `#[chemin(trailing_slash = "...")]` (see
+//! [TrailingSlash]): with `"strict"` (the default), a url only corresponds to a route if its trailing slash matches; with `"ignore"`,
+//! the trailing slash doesn't matter; and with `"redirect"`, [Chemin::parse_or_redirect] returns the url to which the client should
+//! be redirected. Generated urls always follow the path of the route:
+//!
+//! ```
+//! use chemin::{Chemin, ParseOrRedirect};
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! ##[chemin(trailing_slash = "redirect")]
+//! enum Route {
+//!     ##[route("/about")]
+//!     About,
+//! }
+//!
+//! assert_eq!(Route::parse_or_redirect("/about", true), Some(ParseOrRedirect::Route(Route::About, vec![])));
+//! assert_eq!(Route::parse_or_redirect("/about/", true), Some(ParseOrRedirect::Redirect(String::from("/about"))));
+//! assert_eq!(Route::parse("/about/", true), None);
+//! ```
+//!
+//! With `#[chemin(case_insensitive)]`, the static segments of the routes of an enum (but not the url parameters, nor the static text
+//! of segments mixing static text and url parameters) are matched case-insensitively. Generated urls always follow the case of the
+//! path of the route, and [Chemin::parse_or_redirect] returns it for urls with a different case:
+//!
+//! ```
+//! use chemin::{Chemin, ParseOrRedirect};
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! ##[chemin(case_insensitive)]
+//! enum Route {
+//!     ##[route("/about")]
+//!     About,
+//! }
+//!
+//! assert_eq!(Route::parse("/About", true), Some((Route::About, vec![])));
+//! assert_eq!(Route::parse_or_redirect("/About", true), Some(ParseOrRedirect::Redirect(String::from("/about"))));
+//! ```
+//!
+//! To also redirect urls whose percent-encoding, query string or slashes differ from the generated ones, use
+//! [Chemin::canonicalize].
+//!
+//! Routes can't be ambiguous: if some url could be matched by two different routes (for example `"/hello/:"` and
+//! `"/hello/about"`), a compile error is emitted, unless these routes are defined for different locales or have different
+//! priorities. The priority of a route is set with `#[route(priority = ...)]` (it is 0 by default): when parsing a url, the
+//! matching routes are tried by decreasing priority (and in the order of declaration for equal priorities), and if a route
+//! fails to parse (for example because a url parameter is invalid), the next one is tried:
+//!
+//! ```
+//! use chemin::Chemin;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/post/:")]
+//!     ##[route(priority = 1)]
+//!     Post(u32),
+//!
+//!     ##[route("/post/:")]
+//!     PostBySlug(String),
+//! }
+//!
+//! assert_eq!(Route::parse("/post/12", true), Some((Route::Post(12), vec![])));
+//! assert_eq!(Route::parse("/post/hello", true), Some((Route::PostBySlug(String::from("hello")), vec![])));
+//! ```
+//!
+//! The url parameters can be restricted with a constraint, which is a regex written between `<` and `>` after the name of the
+//! parameter. The whole url parameter, as it appears in the url (that is to say percent-encoded), must match it, otherwise the route
+//! isn't matched, so it doesn't conflict with routes whose static segments don't match it. The url generation fails (with
+//! [GenerateError::InvalidParam]) if a url parameter doesn't match its constraint:
+//!
+//! ```
+//! use chemin::Chemin;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/post/new")]
+//!     NewPost,
+//!
+//!     ##[route(r"/post/:id<\d+>")]
+//!     Post { id: u32 },
+//!
+//!     ##[route("/tag/:<[a-z0-9-]+>")]
+//!     Tag(String),
+//! }
+//!
+//! assert_eq!(Route::parse("/post/new", true), Some((Route::NewPost, vec![])));
+//! assert_eq!(Route::parse("/post/12", true), Some((Route::Post { id: 12 }, vec![])));
+//! assert_eq!(Route::parse("/tag/Rust", true), None);
+//! assert_eq!(Route::Tag(String::from("rust")).generate_url(None, true), Some(String::from("/tag/rust")));
+//! assert_eq!(Route::Tag(String::from("Rust")).generate_url(None, true), None);
+//! ```
+//!
+//! The rest of the url can be captured by a catch-all parameter, written with `*` at the end of the path (it is named like the
+//! other url parameters, as in `"/static/*path"`). Its type must implement [CatchAllParam], which is the case of [String],
+//! [PathBuf](std::path::PathBuf) and `Vec<String>`. Each segment of the rest of the url is percent-decoded and percent-encoded
+//! separately. Only a last segment made of `*` and an optional field name is a catch-all: elsewhere, as in `"/files/*/list"` or
+//! `"/static/*.css"`, `*` is a static character. The rest of the url can't be empty, so generating the url of a route whose
+//! catch-all parameter is empty fails with [GenerateError::EmptyParam]:
+//!
+//! ```
+//! use chemin::Chemin;
+//! use std::path::PathBuf;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/static/*path")]
+//!     Static { path: PathBuf },
+//!
+//!     ##[route("/proxy/:/*")]
+//!     Proxy(String, Vec<String>),
+//! }
+//!
+//! assert_eq!(
+//!     Route::parse("/static/css/main%20style.css", true),
+//!     Some((Route::Static { path: PathBuf::from("css/main style.css") }, vec![])),
+//! );
+//! assert_eq!(Route::parse("/static/../secret", true), None); // `PathBuf` rejects the ".." segments
+//! assert_eq!(
+//!     Route::Proxy(String::from("api"), vec![String::from("users"), String::from("John Doe")]).generate_url(None, true),
+//!     Some(String::from("/proxy/api/users/John%20Doe")),
+//! );
+//! ```
+//!
+//! A part of the path can be made optional with an optional group, written between `(` and `)?`, which must contain exactly one url
+//! parameter. The field of this parameter must be an [Option]: it is [None] when the group is omitted. When generating a url, the
+//! group is included only if the field is [Some]. Since the generated urls must be parsed back as the same route, a path whose
+//! optional groups could produce several paths matching the same urls is rejected at compile time: for example
+//! `"/archive(/:)?(/:)?"`, where `"/archive/12"` could contain any of the two url parameters, or `"/tag/:tag(/page/:page)?/.."`,
+//! where `"/tag/rust/page/2/in/posts"` could be parsed without the optional group, `"/page/2/posts"` being given to the sub-route:
+//!
+//! ```
+//! use chemin::Chemin;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/blog(/page/:page)?")]
+//!     Blog { page: Option<u32> },
+//! }
+//!
+//! assert_eq!(Route::parse("/blog", true), Some((Route::Blog { page: None }, vec![])));
+//! assert_eq!(Route::parse("/blog/page/2", true), Some((Route::Blog { page: Some(2) }, vec![])));
+//! assert_eq!(Route::Blog { page: None }.generate_url(None, true), Some(String::from("/blog")));
+//! assert_eq!(Route::Blog { page: Some(2) }.generate_url(None, true), Some(String::from("/blog/page/2")));
+//! ```
+//!
+//! A segment can also mix static text and url parameters (as in `"/file-:id.json"`). A url parameter of such a segment matches as
+//! few characters as possible (and at least one character if it has no constraint), so if a segment contains several url parameters
+//! which aren't separated by static text, you should give them constraints. After static text, `:` only starts a url parameter when
+//! it is followed by a field name, a constraint or the end of the segment: it is a static character in `"/time/12:30"`, but not in
+//! `"/a:b"`, which contains a url parameter named `b`:
+//!
+//! ```
+//! use chemin::Chemin;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/api/v:version/file-:id.json")]
+//!     File { version: u8, id: u32 },
+//! }
+//!
+//! assert_eq!(Route::parse("/api/v2/file-12.json", true), Some((Route::File { version: 2, id: 12 }, vec![])));
+//! assert_eq!(Route::File { version: 2, id: 12 }.generate_url(None, true), Some(String::from("/api/v2/file-12.json")));
+//! ```
+//!
 //! ## Sub-routes
 //!
 //! But for more complex routers, you're not gonna put everything into a single enum. You can break it up with sub-routes:
@@ -267,8 +430,297 @@
 //! );
 //! ```
 //!
+//! Query parameters can be repeated, with `#[query_param(multiple)]` on a field whose type is a collection (such as [Vec] or
+//! [HashSet](std::collections::HashSet)). Each value is a separate query parameter, unless a separator is defined with
+//! `#[query_param(multiple, separator = ",")]` (in which case the values must not contain the separator: generating the url fails
+//! with [GenerateError::SeparatorInQueryParam] otherwise):
+//!
+//! ```
+//! use chemin::Chemin;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/posts")]
+//!     Posts {
+//!         ##[query_param(multiple)]
+//!         tag: Vec<String>,
+//!         ##[query_param(multiple, separator = ",")]
+//!         authors: Vec<u32>,
+//!     }
+//! }
+//!
+//! let route = Route::Posts {
+//!     tag: vec![String::from("rust"), String::from("web")],
+//!     authors: vec![1, 2],
+//! };
+//! assert_eq!(Route::parse("/posts?tag=rust&tag=web&authors=1,2", true), Some((route, vec![])));
+//! assert_eq!(
+//!     Route::Posts { tag: vec![], authors: vec![1, 2] }.generate_url(None, true),
+//!     Some(String::from("/posts?authors=1,2")),
+//! );
+//! ```
+//!
+//! A `bool` field can be declared with `#[query_param(flag)]`: it is `true` if the query parameter is present, whatever its value
+//! (as in `?debug`, `?debug=`, `?debug=1` or even `?debug=0`), and `false` if it is absent. When generating a url, the query
+//! parameter is only added if the field is `true`, without a value:
+//!
+//! ```
+//! use chemin::Chemin;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/dashboard")]
+//!     Dashboard {
+//!         ##[query_param(flag)]
+//!         debug: bool,
+//!     }
+//! }
+//!
+//! assert_eq!(Route::parse("/dashboard?debug", true), Some((Route::Dashboard { debug: true }, vec![])));
+//! assert_eq!(Route::parse("/dashboard?debug=1", true), Some((Route::Dashboard { debug: true }, vec![])));
+//! assert_eq!(Route::parse("/dashboard?debug=0", true), Some((Route::Dashboard { debug: true }, vec![])));
+//! assert_eq!(Route::parse("/dashboard", true), Some((Route::Dashboard { debug: false }, vec![])));
+//! assert_eq!(Route::Dashboard { debug: true }.generate_url(None, true), Some(String::from("/dashboard?debug")));
+//! assert_eq!(Route::Dashboard { debug: false }.generate_url(None, true), Some(String::from("/dashboard")));
+//! ```
+//!
+//! The key of a query parameter is the name of its field by default. Another key can be defined with `rename = "..."`, and other
+//! keys accepted when parsing (for example legacy ones) with `alias = "..."`. The primary key is always the one used when
+//! generating a url:
+//!
+//! ```
+//! use chemin::Chemin;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/posts")]
+//!     Posts {
+//!         ##[query_param(default = 20, rename = "pageSize", alias = "per_page")]
+//!         page_size: u32,
+//!     }
+//! }
+//!
+//! assert_eq!(Route::parse("/posts?per_page=50", true), Some((Route::Posts { page_size: 50 }, vec![])));
+//! assert_eq!(
+//!     Route::Posts { page_size: 50 }.generate_url(None, true),
+//!     Some(String::from("/posts?pageSize=50")),
+//! );
+//! ```
+//!
 //! If you use sub-routes, you can have query parameters defined at any level of the "route tree", and they will all share the same
-//! query string.
+//! query string. Because of that, a compilation error is emitted if a route and its sub-route (or their own sub-routes) use the same
+//! key (this check isn't done for generic enums):
+//!
+//! ```compile_fail
+//! use chemin::Chemin;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/posts/..sub_route")]
+//!     Posts {
+//!         sub_route: PostsRoute,
+//!         ##[query_param(optional, alias = "p")]
+//!         page: Option<u32>,
+//!     }
+//! }
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum PostsRoute {
+//!     ##[route("/:id")]
+//!     Post {
+//!         id: u32,
+//!         ##[query_param(optional, rename = "p")]
+//!         paragraph: Option<u32>,
+//!     }
+//! }
+//! ```
+//!
+//! To avoid such conflicts, or to use the same sub-route type at several levels of the route tree, the keys of a sub-route can be
+//! prefixed with `#[route("...", query_prefix = "...")]`: the prefix is added to its keys when generating a url, and the sub-route
+//! only sees the query parameters whose keys start with it (without the prefix) when parsing one:
+//!
+//! ```
+//! use chemin::Chemin;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/users/..list", query_prefix = "list.")]
+//!     Users {
+//!         list: ListRoute,
+//!         ##[query_param(default = 1)]
+//!         page: u32,
+//!     }
+//! }
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum ListRoute {
+//!     ##[route("/list")]
+//!     List {
+//!         ##[query_param(default = 1)]
+//!         page: u32,
+//!     }
+//! }
+//!
+//! let route = Route::Users { list: ListRoute::List { page: 3 }, page: 2 };
+//! assert_eq!(route.generate_url(None, true), Some(String::from("/users/list?page=2&list.page=3")));
+//! assert_eq!(Route::parse("/users/list?page=2&list.page=3", true), Some((route, vec![])));
+//! ```
+//!
+//! With the `serde` feature, a field whose type implements [Serialize](https://docs.rs/serde/latest/serde/trait.Serialize.html) and
+//! [Deserialize](https://docs.rs/serde/latest/serde/trait.Deserialize.html) (typically a struct grouping the filters of a search
+//! page) can be declared with `#[query_param(flatten)]`. It is parsed from all the query parameters and serialized back with
+//! [serde_qs](https://docs.rs/serde_qs), which supports the bracket notation for nested values (as in `filter[price][min]=10`). It
+//! claims the query parameters whose key (before the bracket notation) is the name of one of its fields, or all of them if its
+//! type doesn't declare fields (as a map). Since these keys are only known at runtime, they are not checked against the keys of the
+//! other query parameters:
+//!
+//! ```
+//! # #[cfg(feature = "serde")]
+//! # {
+//! use chemin::Chemin;
+//! use serde::{Deserialize, Serialize};
+//!
+//! ##[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+//! struct Filter {
+//!     price: Price,
+//!     brand: Option<String>,
+//! }
+//!
+//! ##[derive(Serialize, Deserialize, PartialEq, Eq, Debug)]
+//! struct Price {
+//!     min: Option<u32>,
+//!     max: Option<u32>,
+//! }
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/search")]
+//!     Search {
+//!         ##[query_param(flatten)]
+//!         filter: Filter,
+//!     }
+//! }
+//!
+//! let route = Route::Search {
+//!     filter: Filter {
+//!         price: Price { min: Some(10), max: None },
+//!         brand: None,
+//!     },
+//! };
+//! assert_eq!(Route::parse("/search?price[min]=10", true), Some((route, vec![])));
+//! # }
+//! ```
+//!
+//! By default, the query parameters which aren't claimed by any field of the route are ignored, and thus lost when generating the url
+//! of the route again. This can be changed with `#[chemin(unknown_query = "...")]` (see [UnknownQuery]): `"reject"` makes such
+//! urls correspond to no route, and `"capture"` collects those query parameters in the `#[query_rest]` field of the route, so that
+//! they are preserved (for example tracking parameters across redirections). The field is a `Vec<(String, Option<String>)>`, where
+//! the value of a query parameter without "=" (as in `?utm`) is [None], so that it is generated again without a value. Since the query string is shared by the route tree, the
+//! policy of the type on which the url is parsed applies, and the `#[query_rest]` field can be defined at any level:
+//!
+//! ```
+//! use chemin::Chemin;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! ##[chemin(unknown_query = "capture")]
+//! enum Route {
+//!     ##[route("/posts/:id")]
+//!     Post {
+//!         id: u32,
+//!         ##[query_param(optional)]
+//!         comment: Option<u32>,
+//!         ##[query_rest]
+//!         extra: Vec<(String, Option<String>)>,
+//!     }
+//! }
+//!
+//! let route = Route::Post {
+//!     id: 1,
+//!     comment: Some(3),
+//!     extra: vec![(String::from("utm_source"), Some(String::from("newsletter"))), (String::from("ab"), None)],
+//! };
+//! assert_eq!(Route::parse("/posts/1?utm_source=newsletter&comment=3&ab", true), Some((route, vec![])));
+//! ```
+//!
+//! Below a `query_prefix`, a `#[query_rest]` field only collects the unclaimed query parameters whose keys start with the prefix
+//! (without it), since its query parameters are generated again with the prefix. The other unclaimed query parameters are then
+//! ignored, as for a route without `#[query_rest]` field: to preserve them too, define the `#[query_rest]` field above the
+//! `query_prefix`, in the variant declaring it.
+//!
+//! In the generated urls, the query parameters of a route come before those of its sub-route. Another order can be chosen with
+//! `#[chemin(query_order = "...")]` (see [QueryOrder]): `"declaration"` follows the order of the fields, those of a sub-route being
+//! at the position of its field, and `"alphabetical"` sorts the query parameters by key, so that the urls don't change when the route
+//! tree is refactored (for example when they are used as cache keys):
+//!
+//! ```
+//! use chemin::Chemin;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! ##[chemin(query_order = "alphabetical")]
+//! enum Route {
+//!     ##[route("/search")]
+//!     Search {
+//!         ##[query_param]
+//!         query: String,
+//!         ##[query_param(optional)]
+//!         category: Option<String>,
+//!     }
+//! }
+//!
+//! let route = Route::Search { query: String::from("rust"), category: Some(String::from("books")) };
+//! assert_eq!(route.generate_url(None, true), Some(String::from("/search?category=books&query=rust")));
+//! ```
+//!
+//! ## Custom parameter formats
+//!
+//! By default, url parameters and query parameters are parsed with [FromStr](std::str::FromStr) and formatted with
+//! [Display](std::fmt::Display). To use another format without having to define a new type, you can add
+//! `#[param(with = "module")]` to the field, where `module` is the path of a module containing two functions:
+//! `parse(&str) -> Result<T, E>` (where `E` implements [Display](std::fmt::Display)) and `format(&T) -> String`.
+//!
+//! ```
+//! use chemin::Chemin;
+//!
+//! mod iso_date {
+//!     ##[derive(PartialEq, Eq, Debug)]
+//!     pub struct Date {
+//!         pub year: u16,
+//!         pub month: u8,
+//!         pub day: u8,
+//!     }
+//!
+//!     pub fn parse(value: &str) -> Result<Date, String> {
+//!         let parts = value.split('-').collect::<Vec<_>>();
+//!         match parts[..] {
+//!             [year, month, day] => Ok(Date {
+//!                 year: year.parse().map_err(|_| String::from("invalid year"))?,
+//!                 month: month.parse().map_err(|_| String::from("invalid month"))?,
+//!                 day: day.parse().map_err(|_| String::from("invalid day"))?,
+//!             }),
+//!             _ => Err(String::from("invalid date")),
+//!         }
+//!     }
+//!
+//!     pub fn format(date: &Date) -> String {
+//!         format!("{:04}-{:02}-{:02}", date.year, date.month, date.day)
+//!     }
+//! }
+//!
+//! use iso_date::Date;
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route("/archive/:")]
+//!     Archive(#[param(with = "iso_date")] Date),
+//! }
+//!
+//! let date = Date { year: 2024, month: 1, day: 31 };
+//! assert_eq!(Route::Archive(date).generate_url(None, true), Some(String::from("/archive/2024-01-31")));
+//! assert_eq!(
+//!     Route::parse("/archive/2024-01-31", true),
+//!     Some((Route::Archive(Date { year: 2024, month: 1, day: 31 }), vec![])),
+//! );
+//! ```
 //!
 //! ## Internationalization (i18n)
 //!
@@ -357,6 +809,30 @@
 //!     Some(String::from("/selectionner/couleur/0/255/0")),
 //! );
 //! ```
+//!
+//! When a route isn't translated for the requested locale, other locales can be tried instead, by using a [LocaleFallback] policy:
+//!
+//! ```
+//! use chemin::{Chemin, LocaleFallback};
+//!
+//! ##[derive(Chemin, PartialEq, Eq, Debug)]
+//! enum Route {
+//!     ##[route(en => "/about")]
+//!     ##[route(fr => "/a-propos")]
+//!     About,
+//! }
+//!
+//! // "fr-CA" is truncated to "fr", and "en" is used when nothing else is available
+//! let fallback = LocaleFallback::with_default_locales(&["en"]);
+//! assert_eq!(
+//!     Route::About.generate_url_with_fallback(Some("fr-CA"), &fallback, true),
+//!     Some(String::from("/a-propos")),
+//! );
+//! assert_eq!(
+//!     Route::About.generate_url_with_fallback(Some("es"), &fallback, true),
+//!     Some(String::from("/about")),
+//! );
+//! ```
 
 extern crate self as chemin;
 
@@ -365,17 +841,41 @@ extern crate self as chemin;
 /// To learn how to use it, see [the root of the documentation](index.html).
 pub use chemin_macros::Chemin;
 
+mod canonical;
+pub use canonical::*;
+mod catch_all;
+pub use catch_all::*;
+mod errors;
+pub use errors::*;
+#[cfg(feature = "serde")]
+mod flattened_query_params;
+#[cfg(feature = "serde")]
+pub use flattened_query_params::*;
+#[cfg(not(feature = "serde"))]
+mod flattened_query_params_without_serde;
+#[cfg(not(feature = "serde"))]
+pub use flattened_query_params_without_serde::*;
+mod locales;
+pub use locales::*;
+mod pattern;
+pub use pattern::*;
+mod query_params;
+pub use query_params::*;
+mod route_info;
+pub use route_info::*;
+mod trailing_slash;
+pub use trailing_slash::*;
+
 use percent_encoding::AsciiSet;
 use qstring::QString;
 use smallvec::{SmallVec, ToSmallVec};
 use std::borrow::Cow;
+use std::cell::Cell;
 use std::fmt::Display;
 
 #[doc(hidden)]
 pub mod deps {
-    pub use once_cell;
     pub use qstring;
-    pub use route_recognizer;
 }
 
 /// Trait to derive to build a enum-based router.
@@ -383,6 +883,26 @@ pub mod deps {
 /// This trait is not meant to be implemented directly (although you can). To learn how to derive it, see
 /// [the root of the documentation](index.html).
 pub trait Chemin: Sized {
+    /// The description of the routes defined by this type, one for each variant, in the order of declaration. It can be used for
+    /// debugging or documentation purposes.
+    ///
+    /// It is empty by default, for manual implementations of this trait.
+    const ROUTES: &'static [RouteInfo] = &[];
+
+    /// Calls `visitor` on every route of the route tree of this type, that is to say its routes without sub-route, and the routes of
+    /// the sub-routes (recursively). Each route is visited once for each of its paths, in the order of declaration. See [FlatRoute].
+    ///
+    /// It can be used to list all the urls of an application, or to find paths conflicting with each other across nested types.
+    fn walk_routes(mut visitor: impl FnMut(&FlatRoute)) {
+        route_info::walk_routes(
+            Self::ROUTES,
+            &mut Vec::new(),
+            "",
+            &AcceptedLocales::Any,
+            &mut visitor,
+        );
+    }
+
     /// Parses an url to obtain a route.
     ///
     /// The `url` can contain a query string.
@@ -396,33 +916,110 @@ pub trait Chemin: Sized {
     /// function returns [None]. If not, this function returns a tuple wrapped in [Some], whose first field is the obtained route, and
     /// whose second field is a list of the locales corresponding to this route. Most of the time, it is only one locale, or zero if
     /// no locale was defined for this route.
+    ///
+    /// To know why the parsing failed, use [Chemin::try_parse].
     fn parse(url: &str, decode_params: bool) -> Option<(Self, Vec<Locale>)> {
-        let mut split = url.split('?').peekable();
-        let path = split.next()?;
+        Self::try_parse(url, decode_params).ok()
+    }
 
-        let qstring = if split.peek().is_none() {
-            QString::default()
-        } else {
-            let qstring = split
-                .fold(String::new(), |mut qstring, fragment| {
-                    qstring.push('?');
-                    qstring.push_str(fragment);
-                    qstring
-                })
-                .replace('+', "%20");
-            QString::from(&qstring[..])
+    /// Same as [Chemin::parse], but returns a [ParseError] explaining why the parsing failed, instead of [None].
+    ///
+    /// [ParseError::NotFound] means that the url doesn't correspond to any route, while [ParseError::InvalidField] means that a route
+    /// was found, but that one of its url parameters or query string parameters is missing or invalid. The error message of a
+    /// parameter that failed to parse is the [Display] of the error returned by its parsing function, or `"invalid value"` if this
+    /// error type doesn't implement [Display].
+    fn try_parse(url: &str, decode_params: bool) -> Result<(Self, Vec<Locale>), ParseError> {
+        parse_url(url, decode_params, &Cell::new(false))
+    }
+
+    /// Same as [Chemin::parse], but if the url isn't canonical, returns the canonical url to which the client should be redirected,
+    /// with the same query string. See [ParseOrRedirect].
+    ///
+    /// A url isn't canonical if it corresponds to a route whose trailing slash policy is [TrailingSlash::Redirect], with a trailing
+    /// slash where the path of the route has none (or the reverse), or if it corresponds to a route of an enum with
+    /// `#[chemin(case_insensitive)]`, with a static segment whose case differs from the path of the route.
+    fn parse_or_redirect(url: &str, decode_params: bool) -> Option<ParseOrRedirect<Self>> {
+        let query = &url[url.find('?').unwrap_or(url.len())..];
+        let non_canonical = Cell::new(false);
+
+        // The generated url follows the trailing slash and the case of the path of the route.
+        let redirect_url = |route: &Self, locales: &[Locale]| {
+            let url = route.generate_url(locales.first().copied(), decode_params)?;
+            let path = &url[..url.find('?').unwrap_or(url.len())];
+            Some(format!("{}{}", path, query))
+        };
+
+        match parse_url::<Self>(url, decode_params, &non_canonical) {
+            Ok((route, locales)) => {
+                if non_canonical.get() {
+                    if let Some(url) = redirect_url(&route, &locales) {
+                        return Some(ParseOrRedirect::Redirect(url));
+                    }
+                }
+
+                Some(ParseOrRedirect::Route(route, locales))
+            }
+
+            Err(ParseError::TrailingSlashMismatch) => {
+                let path = &url[..url.len() - query.len()];
+                let url = format!("{}{}", trailing_slash::toggle_trailing_slash(path)?, query);
+
+                // The static segments of the url may also differ in case from the path of the route, so the redirection is
+                // generated from the route rather than from the url, to avoid a second redirection.
+                let url = match parse_url::<Self>(&url, decode_params, &Cell::new(false)) {
+                    Ok((route, locales)) => redirect_url(&route, &locales).unwrap_or(url),
+                    Err(_) => url,
+                };
+
+                Some(ParseOrRedirect::Redirect(url))
+            }
+
+            Err(_) => None,
+        }
+    }
+
+    /// Parses a url and generates it again for the same locale, to obtain its canonical form, for example to redirect the client to
+    /// it when it differs from the original url. Returns [None] if the url doesn't correspond to any route.
+    ///
+    /// In the canonical url:
+    /// - duplicated slashes are removed;
+    /// - url parameters are percent-encoded (see [Chemin::generate_url]);
+    /// - the trailing slash and the case of static segments follow the path of the route (see [Chemin::parse_or_redirect]);
+    /// - query string parameters are in the order of declaration, and the ones whose value is their default value are removed, as
+    ///   well as the ones which don't belong to the route.
+    fn canonicalize(url: &str) -> Option<Canonical> {
+        let query = &url[url.find('?').unwrap_or(url.len())..];
+        let path = canonical::remove_duplicated_slashes(&url[..url.len() - query.len()]);
+
+        let (route, locales) = match Self::try_parse(&format!("{}{}", path, query), true) {
+            Ok(value) => value,
+
+            Err(ParseError::TrailingSlashMismatch) => {
+                let path = trailing_slash::toggle_trailing_slash(&path)?;
+                Self::parse(&format!("{}{}", path, query), true)?
+            }
+
+            Err(_) => return None,
         };
 
-        Self::parse_with_accepted_locales(path, &AcceptedLocales::Any, decode_params, &qstring)
+        let canonical_url = route.generate_url(locales.first().copied(), true)?;
+
+        Some(Canonical {
+            changed: canonical_url != url,
+            url: canonical_url,
+        })
     }
 
-    /// This function is not meant to be called directly. It is used internally by [Chemin::parse].
+    /// This function is not meant to be called directly. It is used internally by [Chemin::try_parse].
+    ///
+    /// `non_canonical` is set to `true` if the path isn't canonical (see [Chemin::parse_or_redirect]).
     fn parse_with_accepted_locales(
         path: &str,
         accepted_locales: &AcceptedLocales,
         decode_params: bool,
         qstring: &QString,
-    ) -> Option<(Self, Vec<Locale>)>;
+        non_canonical: &Cell<bool>,
+    ) -> Result<(Self, Vec<Locale>), ParseError>;
 
     /// Generates a url from a route.
     ///
@@ -435,12 +1032,27 @@ pub trait Chemin: Sized {
     /// encoded. However, the query string parameters will always be percent-encoded, regardless of the `encode_params` argument.
     /// Additionally, the space character (" ") will be displayed as a "+" in query string parameters.
     ///
-    /// If this route is not defined for the provided `locale`, then this method will return [None].
+    /// If this route is not defined for the provided `locale`, then this method will return [None]. To know which part of the route
+    /// isn't defined for this locale, use [Chemin::try_generate_url].
     fn generate_url(&self, locale: Option<&str>, encode_params: bool) -> Option<String> {
-        let mut qstring = QString::default();
+        self.try_generate_url(locale, encode_params).ok()
+    }
+
+    /// Same as [Chemin::generate_url], but returns a [GenerateError] explaining why the url couldn't be generated, instead of [None].
+    fn try_generate_url(
+        &self,
+        locale: Option<&str>,
+        encode_params: bool,
+    ) -> Result<String, GenerateError> {
+        let mut qstring = QString::default();
+
+        // The query string is shared by the whole route tree, so the order of the outermost type applies.
+        self.generate_url_and_build_qstring(locale, encode_params, Self::QUERY_ORDER, &mut qstring)
+            .map(|mut value| {
+                if Self::QUERY_ORDER == QueryOrder::Alphabetical {
+                    sort_query_params(&mut qstring);
+                }
 
-        self.generate_url_and_build_qstring(locale, encode_params, &mut qstring)
-            .map(|mut value| {
                 if qstring.is_empty() {
                     value
                 } else {
@@ -451,13 +1063,124 @@ pub trait Chemin: Sized {
             })
     }
 
-    /// This method is not meant to be called directly. It is used internally by [Chemin::generate_url].
+    /// Same as [Chemin::generate_url], but if the route (or one of its sub-routes) isn't defined for the provided `locale`, the other
+    /// locales chosen by the `fallback` policy are tried in order, until the url can be generated. See [LocaleFallback].
+    ///
+    /// The same locale is used for the whole route, including its sub-routes, so that the generated url can be parsed back.
+    fn generate_url_with_fallback(
+        &self,
+        locale: Option<&str>,
+        fallback: &LocaleFallback,
+        encode_params: bool,
+    ) -> Option<String> {
+        self.try_generate_url_with_fallback(locale, fallback, encode_params)
+            .ok()
+    }
+
+    /// Same as [Chemin::generate_url_with_fallback], but returns the [GenerateError] obtained for the provided `locale` if no locale
+    /// of the `fallback` policy allowed to generate the url, instead of [None].
+    fn try_generate_url_with_fallback(
+        &self,
+        locale: Option<&str>,
+        fallback: &LocaleFallback,
+        encode_params: bool,
+    ) -> Result<String, GenerateError> {
+        let mut first_error = None;
+
+        for candidate in fallback.candidates(locale) {
+            match self.try_generate_url(candidate, encode_params) {
+                Ok(url) => return Ok(url),
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+
+        Err(first_error.unwrap())
+    }
+
+    /// This method is not meant to be called directly. It is used internally by [Chemin::try_generate_url].
     fn generate_url_and_build_qstring(
         &self,
         locale: Option<&str>,
         encode_params: bool,
+        query_order: QueryOrder,
         qstring: &mut QString,
-    ) -> Option<String>;
+    ) -> Result<String, GenerateError>;
+
+    /// Translates a url into another locale: the url is parsed with [Chemin::parse], and the obtained route is generated again with
+    /// [Chemin::generate_url] for the locale `to`, keeping its url parameters and query string parameters. Url parameters are
+    /// percent-decoded when parsing, and percent-encoded when generating.
+    ///
+    /// If the route corresponding to the url is not specific to any locale, the url is returned unchanged. [None] is returned if the
+    /// url doesn't correspond to any route, or if the route is not defined for the locale `to`.
+    fn translate_url(url: &str, to: &str) -> Option<String> {
+        let (route, locales) = Self::parse(url, true)?;
+
+        if locales.is_empty() {
+            Some(String::from(url))
+        } else {
+            route.generate_url(Some(to), true)
+        }
+    }
+
+    /// Returns the locales for which this route is defined, by intersecting the locales of each level of the route (the route itself,
+    /// and its sub-routes), the same way [Chemin::parse] does. The locales are sorted.
+    ///
+    /// If this route is not specific to any locale, the returned list is empty.
+    ///
+    /// Combined with [negotiate_locale], it can be used to choose the locale in which to display a route to a visitor.
+    fn available_locales(&self) -> Vec<Locale> {
+        let mut available_locales =
+            self.available_locales_with_accepted_locales(&AcceptedLocales::Any);
+        available_locales.sort_unstable();
+        available_locales.dedup();
+        available_locales
+    }
+
+    /// Generates the url of this route for each of its [available locales](Chemin::available_locales), for example to list the
+    /// translations of a page with `<link rel="alternate" hreflang="...">` tags.
+    ///
+    /// Each locale appears once, even when several locales share the same path. The list is sorted by locale, and is empty if this
+    /// route is not specific to any locale. See [Chemin::generate_url] for the `encode_params` argument.
+    fn alternate_urls(&self, encode_params: bool) -> Vec<(Locale, String)> {
+        self.available_locales()
+            .into_iter()
+            .filter_map(|locale| {
+                self.generate_url(Some(locale), encode_params)
+                    .map(|url| (locale, url))
+            })
+            .collect()
+    }
+
+    /// This method is not meant to be called directly. It is used internally by [Chemin::available_locales].
+    fn available_locales_with_accepted_locales(
+        &self,
+        accepted_locales: &AcceptedLocales,
+    ) -> Vec<Locale>;
+
+    /// The policy applied to the query string parameters which aren't claimed by any field of the route, defined with
+    /// `#[chemin(unknown_query = "...")]`. See [UnknownQuery].
+    const UNKNOWN_QUERY: UnknownQuery = UnknownQuery::Ignore;
+
+    /// The order of the query string parameters in the generated urls, defined with `#[chemin(query_order = "...")]`. See
+    /// [QueryOrder].
+    const QUERY_ORDER: QueryOrder = QueryOrder::Tree;
+
+    /// This method is not meant to be called directly. It is used internally by [Chemin::try_parse], to apply the
+    /// [UnknownQuery] policy.
+    ///
+    /// Returns whether a field of this route, or of its sub-routes, claims the query string parameter `key`.
+    fn claims_query_key(&self, _key: &str) -> bool {
+        true
+    }
+
+    /// This method is not meant to be called directly. It is used internally by [Chemin::try_parse], to apply the
+    /// [UnknownQuery] policy.
+    ///
+    /// Gives the unclaimed query string parameters to the `#[query_rest]` field of this route, or of its sub-routes.
+    /// The value of a query string parameter without "=" (as in `?utm`) is [None].
+    fn set_query_rest(&mut self, _query_rest: Vec<(String, Option<String>)>) {}
 }
 
 /// A standard locale code, such as used with <https://developer.mozilla.org/en-US/docs/Web/API/Navigator/language>.
@@ -540,8 +1263,63 @@ fn intersect_locales<'a>(
         .filter(|route_locale| accepted_locales.contains(route_locale))
 }
 
+fn parse_url<T: Chemin>(
+    url: &str,
+    decode_params: bool,
+    non_canonical: &Cell<bool>,
+) -> Result<(T, Vec<Locale>), ParseError> {
+    let mut split = url.split('?').peekable();
+    let path = split.next().ok_or(ParseError::NotFound)?;
+
+    let qstring = if split.peek().is_none() {
+        QString::default()
+    } else {
+        let qstring = split
+            .fold(String::new(), |mut qstring, fragment| {
+                qstring.push('?');
+                qstring.push_str(fragment);
+                qstring
+            })
+            .replace('+', "%20");
+        QString::from(&qstring[..])
+    };
+
+    let (mut route, locales) = T::parse_with_accepted_locales(
+        path,
+        &AcceptedLocales::Any,
+        decode_params,
+        &qstring,
+        non_canonical,
+    )?;
+
+    // The query string is shared by the whole route tree, so the policy of the outermost type applies.
+    match T::UNKNOWN_QUERY {
+        UnknownQuery::Ignore => {}
+
+        UnknownQuery::Reject => {
+            if let Some((key, _)) = qstring
+                .to_pairs()
+                .into_iter()
+                .find(|(key, _)| !route.claims_query_key(key))
+            {
+                return Err(ParseError::UnknownQueryParam(String::from(key)));
+            }
+        }
+
+        UnknownQuery::Capture => {
+            let query_rest = into_pairs_with_flags(qstring)
+                .into_iter()
+                .filter(|(key, _)| !route.claims_query_key(key))
+                .collect();
+            route.set_query_rest(query_rest);
+        }
+    }
+
+    Ok((route, locales))
+}
+
 #[doc(hidden)]
-pub fn decode_param(param: &str) -> Option<Cow<str>> {
+pub fn decode_param(param: &str) -> Option<Cow<'_, str>> {
     percent_encoding::percent_decode_str(param)
         .decode_utf8()
         .ok()
@@ -638,6 +1416,7 @@ fn test_derive() {
     }
 
     #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[allow(clippy::enum_variant_names)]
     enum Route {
         #[route("/")]
         Home,
@@ -865,3 +1644,2283 @@ fn test_derive() {
         ))
     );
 }
+
+#[test]
+fn test_derive_parse_errors() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route("/hello/:name/:age")]
+        Hello { name: String, age: u8 },
+
+        #[route(en => "/sub-route/..")]
+        WithSubRoute(SubRoute),
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum SubRoute {
+        #[route("/with-params/:id")]
+        WithParams {
+            id: u8,
+            #[query_param]
+            page: u8,
+        },
+
+        #[route(fr => "/bonjour")]
+        Hello,
+    }
+
+    let hello = RouteVariant {
+        enum_name: "Route",
+        variant: "Hello",
+    };
+    let with_sub_route = RouteVariant {
+        enum_name: "Route",
+        variant: "WithSubRoute",
+    };
+    let with_params = RouteVariant {
+        enum_name: "SubRoute",
+        variant: "WithParams",
+    };
+
+    assert_eq!(
+        Route::try_parse("/unknown", true),
+        Err(ParseError::NotFound)
+    );
+    assert_eq!(
+        Route::try_parse("/hello/john/thirty", true),
+        Err(ParseError::InvalidField {
+            variants: vec![hello],
+            field: "age",
+            kind: FieldErrorKind::InvalidParam(String::from("invalid digit found in string")),
+        }),
+    );
+    assert_eq!(
+        Route::try_parse("/hello/%FF/30", true),
+        Err(ParseError::InvalidField {
+            variants: vec![hello],
+            field: "name",
+            kind: FieldErrorKind::InvalidEncoding,
+        }),
+    );
+    assert_eq!(
+        Route::try_parse("/hello/%FF/30", false),
+        Ok((
+            Route::Hello {
+                name: String::from("%FF"),
+                age: 30
+            },
+            vec![]
+        )),
+    );
+
+    // Errors from sub-routes
+    assert_eq!(
+        Route::try_parse("/sub-route/bonjour", true),
+        Err(ParseError::NotFound),
+    );
+    assert_eq!(
+        Route::try_parse("/sub-route/with-params/256?page=1", true),
+        Err(ParseError::InvalidField {
+            variants: vec![with_sub_route, with_params],
+            field: "id",
+            kind: FieldErrorKind::InvalidParam(String::from(
+                "number too large to fit in target type"
+            )),
+        }),
+    );
+    assert_eq!(
+        Route::try_parse("/sub-route/with-params/1", true),
+        Err(ParseError::InvalidField {
+            variants: vec![with_sub_route, with_params],
+            field: "page",
+            kind: FieldErrorKind::MissingQueryParam,
+        }),
+    );
+    assert_eq!(
+        Route::try_parse("/sub-route/with-params/1?page=first", true),
+        Err(ParseError::InvalidField {
+            variants: vec![with_sub_route, with_params],
+            field: "page",
+            kind: FieldErrorKind::InvalidQueryParam(String::from("invalid digit found in string")),
+        }),
+    );
+}
+
+#[test]
+fn test_derive_parse_errors_without_display() {
+    use std::fmt::{self, Formatter};
+    use std::str::FromStr;
+
+    #[derive(PartialEq, Eq, Debug)]
+    struct Slug(String);
+
+    impl FromStr for Slug {
+        type Err = ();
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            if s.bytes()
+                .all(|byte| byte.is_ascii_lowercase() || byte == b'-')
+            {
+                Ok(Self(s.to_owned()))
+            } else {
+                Err(())
+            }
+        }
+    }
+
+    impl Display for Slug {
+        fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+            write!(f, "{}", self.0)
+        }
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route("/post/:slug")]
+        Post {
+            slug: Slug,
+            #[query_param(optional)]
+            tag: Option<Slug>,
+        },
+    }
+
+    let post = RouteVariant {
+        enum_name: "Route",
+        variant: "Post",
+    };
+
+    assert_eq!(
+        Route::parse("/post/hello-world?tag=rust", true),
+        Some((
+            Route::Post {
+                slug: Slug(String::from("hello-world")),
+                tag: Some(Slug(String::from("rust"))),
+            },
+            vec![]
+        )),
+    );
+    assert_eq!(
+        Route::try_parse("/post/Hello", true),
+        Err(ParseError::InvalidField {
+            variants: vec![post],
+            field: "slug",
+            kind: FieldErrorKind::InvalidParam(String::from("invalid value")),
+        }),
+    );
+    assert_eq!(
+        Route::try_parse("/post/hello?tag=Rust", true),
+        Err(ParseError::InvalidField {
+            variants: vec![post],
+            field: "tag",
+            kind: FieldErrorKind::InvalidQueryParam(String::from("invalid value")),
+        }),
+    );
+}
+
+#[test]
+fn test_derive_generate_errors() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route(en => "/about")]
+        #[route(fr => "/a-propos")]
+        About,
+
+        #[route(en, fr => "/sub-route/..")]
+        WithSubRoute(SubRoute),
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum SubRoute {
+        #[route("/home")]
+        Home,
+
+        #[route(fr => "/bonjour")]
+        Hello,
+
+        #[route("/tags")]
+        Tags {
+            #[query_param(multiple, separator = ",")]
+            tags: Vec<String>,
+        },
+    }
+
+    let about = RouteVariant {
+        enum_name: "Route",
+        variant: "About",
+    };
+    let with_sub_route = RouteVariant {
+        enum_name: "Route",
+        variant: "WithSubRoute",
+    };
+    let hello = RouteVariant {
+        enum_name: "SubRoute",
+        variant: "Hello",
+    };
+
+    assert_eq!(
+        Route::About.try_generate_url(Some("fr"), true),
+        Ok(String::from("/a-propos")),
+    );
+    assert_eq!(
+        Route::About.try_generate_url(Some("de"), true),
+        Err(GenerateError::MissingLocale {
+            variants: vec![about],
+            locale: Some(String::from("de")),
+        }),
+    );
+    assert_eq!(
+        Route::About.try_generate_url(None, true),
+        Err(GenerateError::MissingLocale {
+            variants: vec![about],
+            locale: None,
+        }),
+    );
+
+    assert_eq!(
+        Route::WithSubRoute(SubRoute::Home).try_generate_url(Some("de"), true),
+        Err(GenerateError::MissingLocale {
+            variants: vec![with_sub_route],
+            locale: Some(String::from("de")),
+        }),
+    );
+    assert_eq!(
+        Route::WithSubRoute(SubRoute::Hello).try_generate_url(Some("en"), true),
+        Err(GenerateError::MissingLocale {
+            variants: vec![with_sub_route, hello],
+            locale: Some(String::from("en")),
+        }),
+    );
+
+    let tags = |tags: &[&str]| {
+        Route::WithSubRoute(SubRoute::Tags {
+            tags: tags.iter().map(|tag| String::from(*tag)).collect(),
+        })
+    };
+    assert_eq!(
+        tags(&["a b", "c"]).try_generate_url(Some("en"), true),
+        Ok(String::from("/sub-route/tags?tags=a+b,c")),
+    );
+    assert_eq!(
+        tags(&["a", "b,c"]).try_generate_url(Some("en"), true),
+        Err(GenerateError::SeparatorInQueryParam {
+            variants: vec![
+                with_sub_route,
+                RouteVariant {
+                    enum_name: "SubRoute",
+                    variant: "Tags",
+                },
+            ],
+            field: "tags",
+            separator: ",",
+            value: String::from("b,c"),
+        }),
+    );
+}
+
+#[test]
+fn test_derive_generate_url_with_fallback() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[allow(clippy::enum_variant_names)]
+    enum Route {
+        #[route("/")]
+        Home,
+
+        #[route(en => "/about")]
+        #[route(fr => "/a-propos")]
+        About,
+
+        #[route(en => "/sub-route/..")]
+        #[route(fr => "/sous-route/..")]
+        WithSubRoute(SubRoute),
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum SubRoute {
+        #[route(en => "/hello")]
+        #[route(fr_CA => "/allo")]
+        Hello,
+    }
+
+    let fallback = LocaleFallback::new();
+    assert_eq!(
+        Route::Home.generate_url_with_fallback(Some("es"), &fallback, true),
+        Some(String::from("/")),
+    );
+    assert_eq!(
+        Route::About.generate_url_with_fallback(Some("fr-CA"), &fallback, true),
+        Some(String::from("/a-propos")),
+    );
+    assert_eq!(
+        Route::About.generate_url_with_fallback(Some("es"), &fallback, true),
+        None,
+    );
+    assert_eq!(
+        Route::About.try_generate_url_with_fallback(Some("es-MX"), &fallback, true),
+        Err(GenerateError::MissingLocale {
+            variants: vec![RouteVariant {
+                enum_name: "Route",
+                variant: "About",
+            }],
+            locale: Some(String::from("es-MX")),
+        }),
+    );
+
+    let fallback = LocaleFallback::with_default_locales(&["en"]);
+    assert_eq!(
+        Route::About.generate_url_with_fallback(Some("es"), &fallback, true),
+        Some(String::from("/about")),
+    );
+    assert_eq!(
+        Route::About.generate_url_with_fallback(None, &fallback, true),
+        Some(String::from("/about")),
+    );
+
+    // "fr-CA" isn't defined in `Route`, and "fr" isn't defined in `SubRoute`, so a locale defined at every level is used
+    assert_eq!(
+        Route::WithSubRoute(SubRoute::Hello).generate_url_with_fallback(
+            Some("fr-CA"),
+            &fallback,
+            true
+        ),
+        Some(String::from("/sub-route/hello")),
+    );
+}
+
+#[test]
+fn test_derive_available_locales() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[allow(clippy::enum_variant_names)]
+    enum Route {
+        #[route("/")]
+        Home,
+
+        #[route(en, en_US => "/about")]
+        #[route(fr => "/a-propos")]
+        About,
+
+        #[route(en, fr => "/sub-route/..")]
+        WithSubRoute(SubRoute),
+
+        #[route("/hello/:name/..sub_route")]
+        WithNamedSubRoute { name: String, sub_route: SubRoute },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum SubRoute {
+        #[route("/home")]
+        Home,
+
+        #[route(fr, fr_FR => "/bonjour")]
+        #[route(de => "/hallo")]
+        Hello,
+    }
+
+    assert_eq!(Route::Home.available_locales(), Vec::<Locale>::new());
+    assert_eq!(Route::About.available_locales(), vec!["en", "en-US", "fr"]);
+    assert_eq!(
+        Route::WithSubRoute(SubRoute::Home).available_locales(),
+        vec!["en", "fr"],
+    );
+    assert_eq!(
+        Route::WithSubRoute(SubRoute::Hello).available_locales(),
+        vec!["fr"],
+    );
+    assert_eq!(
+        Route::WithNamedSubRoute {
+            name: String::from("John"),
+            sub_route: SubRoute::Hello,
+        }
+        .available_locales(),
+        vec!["de", "fr", "fr-FR"],
+    );
+}
+
+#[test]
+fn test_derive_alternate_urls() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route("/")]
+        Home,
+
+        #[route(en, en_US => "/about")]
+        #[route(fr => "/a-propos")]
+        About,
+
+        #[route(en => "/hello/..")]
+        #[route(fr => "/bonjour/..")]
+        Hello(SubRoute),
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum SubRoute {
+        #[route(en => "/:")]
+        #[route(fr, de => "/:/")]
+        Name(String),
+    }
+
+    assert_eq!(Route::Home.alternate_urls(true), vec![]);
+    assert_eq!(
+        Route::About.alternate_urls(true),
+        vec![
+            ("en", String::from("/about")),
+            ("en-US", String::from("/about")),
+            ("fr", String::from("/a-propos")),
+        ],
+    );
+    assert_eq!(
+        Route::Hello(SubRoute::Name(String::from("John Doe"))).alternate_urls(true),
+        vec![
+            ("en", String::from("/hello/John%20Doe")),
+            ("fr", String::from("/bonjour/John%20Doe/")),
+        ],
+    );
+}
+
+#[test]
+fn test_derive_translate_url() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route("/")]
+        Home,
+
+        #[route(en => "/about")]
+        #[route(fr => "/a-propos")]
+        About,
+
+        #[route(en => "/hello/:name/..sub_route")]
+        #[route(fr => "/bonjour/:name/..sub_route")]
+        Hello { name: String, sub_route: SubRoute },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum SubRoute {
+        #[route("/age")]
+        Age {
+            #[query_param(optional)]
+            age: Option<u8>,
+        },
+
+        #[route(fr => "/seulement-en-francais")]
+        FrenchOnly,
+    }
+
+    assert_eq!(Route::translate_url("/", "fr"), Some(String::from("/")));
+    assert_eq!(
+        Route::translate_url("/a-propos", "en"),
+        Some(String::from("/about"))
+    );
+    assert_eq!(
+        Route::translate_url("/about", "fr"),
+        Some(String::from("/a-propos"))
+    );
+    assert_eq!(Route::translate_url("/about", "es"), None);
+    assert_eq!(Route::translate_url("/unknown", "fr"), None);
+    assert_eq!(
+        Route::translate_url("/bonjour/Jos%C3%A9/age?age=30", "en"),
+        Some(String::from("/hello/Jos%C3%A9/age?age=30")),
+    );
+    assert_eq!(
+        Route::translate_url("/bonjour/Jos%C3%A9/seulement-en-francais", "en"),
+        None,
+    );
+}
+
+#[test]
+fn test_derive_routes() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[allow(dead_code)]
+    enum Route {
+        #[route("/")]
+        Home,
+
+        #[route(en, en_US => "/hello/:/..")]
+        #[route(fr => "/bonjour/:/..")]
+        Hello(String, SubRoute),
+
+        #[route("/hello-with-age/:name/:age")]
+        HelloWithAge {
+            age: u8,
+            name: String,
+            #[query_param(optional, rename = "nick", alias = "n", alias = "surname")]
+            nickname: Option<String>,
+            #[query_param(default = String::from("default"))]
+            greeting: String,
+        },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(trailing_slash = "ignore")]
+    #[allow(dead_code)]
+    enum SubRoute {
+        #[route("/home")]
+        Home {
+            #[query_param]
+            page: u32,
+        },
+    }
+
+    static SUB_ROUTES: &[RouteInfo] = &[RouteInfo {
+        variant: "Home",
+        paths: &[PathInfo {
+            pattern: "/home",
+            locales: &[],
+        }],
+        params: &[],
+        query_params: &[QueryParamInfo {
+            name: "page",
+            key: "page",
+            aliases: &[],
+            ty: "u32",
+            kind: QueryParamKind::Mandatory,
+        }],
+        query_rest: None,
+        sub_route: None,
+        trailing_slash: TrailingSlash::Ignore,
+    }];
+
+    assert_eq!(SubRoute::ROUTES, SUB_ROUTES);
+    assert_eq!(
+        Route::ROUTES,
+        &[
+            RouteInfo {
+                variant: "Home",
+                paths: &[PathInfo {
+                    pattern: "/",
+                    locales: &[],
+                }],
+                params: &[],
+                query_params: &[],
+                query_rest: None,
+                sub_route: None,
+                trailing_slash: TrailingSlash::Strict,
+            },
+            RouteInfo {
+                variant: "Hello",
+                paths: &[
+                    PathInfo {
+                        pattern: "/hello/:/..",
+                        locales: &["en", "en-US"],
+                    },
+                    PathInfo {
+                        pattern: "/bonjour/:/..",
+                        locales: &["fr"],
+                    },
+                ],
+                params: &[ParamInfo {
+                    name: "0",
+                    ty: "String",
+                }],
+                query_params: &[],
+                query_rest: None,
+                sub_route: Some(SubRouteInfo {
+                    name: "1",
+                    ty: "SubRoute",
+                    routes: SUB_ROUTES,
+                    query_prefix: None,
+                }),
+                trailing_slash: TrailingSlash::Strict,
+            },
+            RouteInfo {
+                variant: "HelloWithAge",
+                paths: &[PathInfo {
+                    pattern: "/hello-with-age/:name/:age",
+                    locales: &[],
+                }],
+                params: &[
+                    ParamInfo {
+                        name: "age",
+                        ty: "u8",
+                    },
+                    ParamInfo {
+                        name: "name",
+                        ty: "String",
+                    },
+                ],
+                query_params: &[
+                    QueryParamInfo {
+                        name: "nickname",
+                        key: "nick",
+                        aliases: &["n", "surname"],
+                        ty: "Option<String>",
+                        kind: QueryParamKind::Optional,
+                    },
+                    QueryParamInfo {
+                        name: "greeting",
+                        key: "greeting",
+                        aliases: &[],
+                        ty: "String",
+                        kind: QueryParamKind::WithDefaultValue("String::from(\"default\")"),
+                    },
+                ],
+                query_rest: None,
+                sub_route: None,
+                trailing_slash: TrailingSlash::Strict,
+            },
+        ],
+    );
+}
+
+#[test]
+fn test_derive_walk_routes() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[allow(dead_code, clippy::enum_variant_names)]
+    enum Route {
+        #[route("/")]
+        Home,
+
+        #[route(en, fr => "/with-sub-route/..")]
+        WithSubRoute(SubRoute),
+
+        #[route("/hello/:name/..sub_route")]
+        HelloWithSubRoute { name: String, sub_route: SubRoute },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[allow(dead_code)]
+    enum SubRoute {
+        #[route("/")]
+        Home,
+
+        #[route(fr_FR, fr => "/bonjour")]
+        #[route(de => "/hallo")]
+        Hello,
+    }
+
+    let mut flat_routes = Vec::new();
+    Route::walk_routes(|flat_route| {
+        flat_routes.push((
+            flat_route
+                .routes
+                .iter()
+                .map(|route| route.variant)
+                .collect::<Vec<_>>(),
+            flat_route.pattern.clone(),
+            flat_route.locales.clone(),
+        ))
+    });
+
+    assert_eq!(
+        flat_routes,
+        vec![
+            (vec!["Home"], String::from("/"), vec![]),
+            (
+                vec!["WithSubRoute", "Home"],
+                String::from("/with-sub-route/"),
+                vec!["en", "fr"]
+            ),
+            (
+                vec!["WithSubRoute", "Hello"],
+                String::from("/with-sub-route/bonjour"),
+                vec!["fr"]
+            ),
+            (
+                vec!["HelloWithSubRoute", "Home"],
+                String::from("/hello/:name/"),
+                vec![]
+            ),
+            (
+                vec!["HelloWithSubRoute", "Hello"],
+                String::from("/hello/:name/bonjour"),
+                vec!["fr", "fr-FR"]
+            ),
+            (
+                vec!["HelloWithSubRoute", "Hello"],
+                String::from("/hello/:name/hallo"),
+                vec!["de"]
+            ),
+        ],
+    );
+}
+
+#[test]
+fn test_derive_priority() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route("/post/:")]
+        #[route(priority = 1)]
+        Post(u8),
+
+        #[route("/post/new")]
+        NewPost,
+
+        #[route("/post/:")]
+        #[route(priority = -1)]
+        PostBySlug(String),
+
+        #[route(en => "/sub-route/..")]
+        A(SubRouteA),
+
+        #[route("/sub-route/..")]
+        #[route(priority = -1)]
+        B(SubRouteB),
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum SubRouteA {
+        #[route("/a/:")]
+        A(u8),
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum SubRouteB {
+        #[route("/b")]
+        B,
+    }
+
+    assert_eq!(
+        Route::parse("/post/12", true),
+        Some((Route::Post(12), vec![]))
+    );
+    assert_eq!(
+        Route::parse("/post/new", true),
+        Some((Route::NewPost, vec![]))
+    );
+    assert_eq!(
+        Route::parse("/post/hello", true),
+        Some((Route::PostBySlug(String::from("hello")), vec![])),
+    );
+
+    // The first route matching the url fails to parse, so the next one is tried
+    assert_eq!(
+        Route::parse("/sub-route/b", true),
+        Some((Route::B(SubRouteB::B), vec![])),
+    );
+
+    // When no route can be parsed, the first error which isn't `ParseError::NotFound` is returned
+    assert_eq!(
+        Route::try_parse("/sub-route/a/256", true),
+        Err(ParseError::InvalidField {
+            variants: vec![
+                RouteVariant {
+                    enum_name: "Route",
+                    variant: "A",
+                },
+                RouteVariant {
+                    enum_name: "SubRouteA",
+                    variant: "A",
+                },
+            ],
+            field: "0",
+            kind: FieldErrorKind::InvalidParam(String::from(
+                "number too large to fit in target type"
+            )),
+        }),
+    );
+}
+
+#[test]
+fn test_derive_param_with() {
+    mod hex {
+        use std::num::ParseIntError;
+
+        pub fn parse(value: &str) -> Result<u32, ParseIntError> {
+            u32::from_str_radix(value, 16)
+        }
+
+        pub fn format(value: &u32) -> String {
+            format!("{:x}", value)
+        }
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route("/color/:")]
+        Color(#[param(with = "hex")] u32),
+
+        #[route("/item/:id")]
+        Item {
+            #[param(with = "hex")]
+            id: u32,
+            #[query_param(optional)]
+            #[param(with = "hex")]
+            parent: Option<u32>,
+            #[query_param(default = 0)]
+            #[param(with = "hex")]
+            offset: u32,
+        },
+    }
+
+    assert_eq!(
+        Route::parse("/color/ff00ff", true),
+        Some((Route::Color(0xff00ff), vec![]))
+    );
+    assert_eq!(
+        Route::Color(0xff00ff).generate_url(None, true),
+        Some(String::from("/color/ff00ff"))
+    );
+
+    let item = Route::Item {
+        id: 0xabc,
+        parent: Some(0x1f),
+        offset: 0x10,
+    };
+    assert_eq!(
+        item.generate_url(None, true),
+        Some(String::from("/item/abc?parent=1f&offset=10"))
+    );
+    assert_eq!(
+        Route::parse("/item/abc?parent=1f&offset=10", true),
+        Some((item, vec![]))
+    );
+
+    assert_eq!(
+        Route::try_parse("/color/red", true),
+        Err(ParseError::InvalidField {
+            variants: vec![RouteVariant {
+                enum_name: "Route",
+                variant: "Color",
+            }],
+            field: "0",
+            kind: FieldErrorKind::InvalidParam(String::from("invalid digit found in string")),
+        }),
+    );
+    assert_eq!(
+        Route::try_parse("/item/abc?parent=xyz", true),
+        Err(ParseError::InvalidField {
+            variants: vec![RouteVariant {
+                enum_name: "Route",
+                variant: "Item",
+            }],
+            field: "parent",
+            kind: FieldErrorKind::InvalidQueryParam(String::from("invalid digit found in string")),
+        }),
+    );
+}
+
+#[test]
+fn test_derive_constraints() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route("/post/new")]
+        NewPost,
+
+        #[route(r"/post/:<\d+>")]
+        Post(u32),
+
+        #[route(r"/post/:<\d+>/comments/..")]
+        Comments(i64, SubRoute),
+
+        #[route("/user/:name<[a-zA-Z0-9%]+>")]
+        User { name: String },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum SubRoute {
+        #[route(r"/:id<\d{1,3}>")]
+        Comment { id: u16 },
+    }
+
+    assert_eq!(
+        Route::parse("/post/new", true),
+        Some((Route::NewPost, vec![]))
+    );
+    assert_eq!(
+        Route::parse("/post/12", true),
+        Some((Route::Post(12), vec![]))
+    );
+    assert_eq!(Route::parse("/post/twelve", true), None);
+    assert_eq!(
+        Route::parse("/post/12/comments/123", true),
+        Some((Route::Comments(12, SubRoute::Comment { id: 123 }), vec![]))
+    );
+    assert_eq!(Route::parse("/post/12/comments/1234", true), None);
+
+    // The constraint is checked on the percent-encoded url parameter
+    assert_eq!(
+        Route::parse("/user/j%C3%A9r%C3%B4me", true),
+        Some((
+            Route::User {
+                name: String::from("jérôme")
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(
+        Route::User {
+            name: String::from("jérôme")
+        }
+        .generate_url(None, true),
+        Some(String::from("/user/j%C3%A9r%C3%B4me"))
+    );
+    assert_eq!(
+        Route::User {
+            name: String::from("jérôme")
+        }
+        .try_generate_url(None, false),
+        Err(GenerateError::InvalidParam {
+            variants: vec![RouteVariant {
+                enum_name: "Route",
+                variant: "User",
+            }],
+            field: "name",
+            constraint: "[a-zA-Z0-9%]+",
+        })
+    );
+
+    assert_eq!(
+        Route::Post(12).generate_url(None, true),
+        Some(String::from("/post/12"))
+    );
+    assert_eq!(
+        Route::Comments(-1, SubRoute::Comment { id: 12 }).try_generate_url(None, true),
+        Err(GenerateError::InvalidParam {
+            variants: vec![RouteVariant {
+                enum_name: "Route",
+                variant: "Comments",
+            }],
+            field: "0",
+            constraint: r"\d+",
+        })
+    );
+    assert_eq!(
+        Route::Comments(12, SubRoute::Comment { id: 1234 }).try_generate_url(None, true),
+        Err(GenerateError::InvalidParam {
+            variants: vec![
+                RouteVariant {
+                    enum_name: "Route",
+                    variant: "Comments",
+                },
+                RouteVariant {
+                    enum_name: "SubRoute",
+                    variant: "Comment",
+                },
+            ],
+            field: "id",
+            constraint: r"\d{1,3}",
+        })
+    );
+}
+
+#[test]
+fn test_derive_catch_all() {
+    use std::path::PathBuf;
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route("/static/about")]
+        #[route(priority = 1)]
+        About,
+
+        #[route("/static/*path")]
+        Static { path: PathBuf },
+
+        #[route(en => "/files/:/*")]
+        #[route(fr => "/fichiers/:/*")]
+        Files(String, String),
+
+        #[route("/sub-route/..")]
+        Nested(SubRoute),
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum SubRoute {
+        #[route("/*")]
+        Rest(Vec<String>),
+    }
+
+    assert_eq!(
+        Route::parse("/static/about", true),
+        Some((Route::About, vec![]))
+    );
+    assert_eq!(
+        Route::parse("/static/css/style.css", true),
+        Some((
+            Route::Static {
+                path: PathBuf::from("css/style.css")
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(Route::parse("/static/", true), None);
+    assert_eq!(
+        Route::try_parse("/static/css/../../secret", true),
+        Err(ParseError::InvalidField {
+            variants: vec![RouteVariant {
+                enum_name: "Route",
+                variant: "Static",
+            }],
+            field: "path",
+            kind: FieldErrorKind::InvalidParam(String::from("the path contains a `..` segment")),
+        })
+    );
+    assert_eq!(
+        Route::parse("/fichiers/photos/2024/%C3%A9t%C3%A9.jpg", true),
+        Some((
+            Route::Files(String::from("photos"), String::from("2024/été.jpg")),
+            vec!["fr"]
+        ))
+    );
+    assert_eq!(
+        Route::parse("/sub-route/a%2Fb/c/", true),
+        Some((
+            Route::Nested(SubRoute::Rest(vec![
+                String::from("a/b"),
+                String::from("c"),
+                String::new(),
+            ])),
+            vec![]
+        ))
+    );
+
+    assert_eq!(
+        Route::Static {
+            path: PathBuf::from("css/style.css")
+        }
+        .generate_url(None, true),
+        Some(String::from("/static/css/style.css"))
+    );
+    assert_eq!(
+        Route::Files(String::from("photos"), String::from("2024/été.jpg"))
+            .generate_url(Some("en"), true),
+        Some(String::from("/files/photos/2024/%C3%A9t%C3%A9.jpg"))
+    );
+    assert_eq!(
+        Route::Files(String::from("photos"), String::from("2024/été.jpg"))
+            .generate_url(Some("fr"), false),
+        Some(String::from("/fichiers/photos/2024/été.jpg"))
+    );
+    assert_eq!(
+        Route::Nested(SubRoute::Rest(vec![String::from("a/b"), String::from("c")]))
+            .generate_url(None, true),
+        Some(String::from("/sub-route/a%2Fb/c"))
+    );
+
+    // An empty rest of the url wouldn't be parsed back.
+    assert_eq!(
+        Route::Static {
+            path: PathBuf::new()
+        }
+        .try_generate_url(None, true),
+        Err(GenerateError::EmptyParam {
+            variants: vec![RouteVariant {
+                enum_name: "Route",
+                variant: "Static",
+            }],
+            field: "path",
+        })
+    );
+    assert_eq!(
+        Route::Files(String::from("photos"), String::new()).try_generate_url(Some("en"), true),
+        Err(GenerateError::EmptyParam {
+            variants: vec![RouteVariant {
+                enum_name: "Route",
+                variant: "Files",
+            }],
+            field: "1",
+        })
+    );
+    assert_eq!(
+        Route::Nested(SubRoute::Rest(vec![])).try_generate_url(None, true),
+        Err(GenerateError::EmptyParam {
+            variants: vec![
+                RouteVariant {
+                    enum_name: "Route",
+                    variant: "Nested",
+                },
+                RouteVariant {
+                    enum_name: "SubRoute",
+                    variant: "Rest",
+                },
+            ],
+            field: "0",
+        })
+    );
+}
+
+#[test]
+fn test_derive_optional_groups() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route(en => "/archive(/:)?(/month/:)?/")]
+        #[route(fr => "/archives(/:)?(/mois/:)?/")]
+        Archive(Option<u16>, Option<u8>),
+
+        #[route(r"/tag/:tag(/page/:page<\d+>)?/in/..sub_route")]
+        Tag {
+            tag: String,
+            page: Option<u32>,
+            sub_route: SubRoute,
+        },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum SubRoute {
+        #[route("/posts")]
+        Posts,
+
+        #[route("/page/:")]
+        Page(String),
+    }
+
+    assert_eq!(
+        Route::parse("/archive/", true),
+        Some((Route::Archive(None, None), vec!["en"]))
+    );
+    assert_eq!(
+        Route::parse("/archive/2024/", true),
+        Some((Route::Archive(Some(2024), None), vec!["en"]))
+    );
+    assert_eq!(
+        Route::parse("/archives/2024/mois/12/", true),
+        Some((Route::Archive(Some(2024), Some(12)), vec!["fr"]))
+    );
+    assert_eq!(
+        Route::parse("/archive/month/12/", true),
+        Some((Route::Archive(None, Some(12)), vec!["en"]))
+    );
+    assert_eq!(Route::parse("/archive/2024/12/", true), None);
+
+    assert_eq!(
+        Route::Archive(None, None).generate_url(Some("en"), true),
+        Some(String::from("/archive/"))
+    );
+    assert_eq!(
+        Route::Archive(Some(2024), None).generate_url(Some("fr"), true),
+        Some(String::from("/archives/2024/"))
+    );
+    assert_eq!(
+        Route::Archive(None, Some(12)).generate_url(Some("en"), true),
+        Some(String::from("/archive/month/12/"))
+    );
+    assert_eq!(
+        Route::Archive(Some(2024), Some(12)).generate_url(Some("en"), true),
+        Some(String::from("/archive/2024/month/12/"))
+    );
+
+    // The longest form is tried first, and the next one is tried when it fails to parse
+    assert_eq!(
+        Route::parse("/tag/rust/page/2/in/posts", true),
+        Some((
+            Route::Tag {
+                tag: String::from("rust"),
+                page: Some(2),
+                sub_route: SubRoute::Posts,
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(
+        Route::parse("/tag/rust/in/page/two", true),
+        Some((
+            Route::Tag {
+                tag: String::from("rust"),
+                page: None,
+                sub_route: SubRoute::Page(String::from("two")),
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(
+        Route::Tag {
+            tag: String::from("rust"),
+            page: Some(2),
+            sub_route: SubRoute::Posts,
+        }
+        .generate_url(None, true),
+        Some(String::from("/tag/rust/page/2/in/posts"))
+    );
+    assert_eq!(
+        Route::Tag {
+            tag: String::from("rust"),
+            page: None,
+            sub_route: SubRoute::Posts,
+        }
+        .generate_url(None, true),
+        Some(String::from("/tag/rust/in/posts"))
+    );
+
+    // The static segment after the optional group keeps the rest of the url given to the sub-route from matching the group.
+    let tag_without_page = Route::Tag {
+        tag: String::from("rust"),
+        page: None,
+        sub_route: SubRoute::Page(String::from("2")),
+    };
+    let url = tag_without_page.generate_url(None, true).unwrap();
+    assert_eq!(url, "/tag/rust/in/page/2");
+    assert_eq!(Route::parse(&url, true), Some((tag_without_page, vec![])));
+}
+
+#[test]
+fn test_derive_mixed_segments() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route(r"/files/file-:id<\d+>.:extension")]
+        File { id: u32, extension: String },
+
+        #[route("/api/v:/..")]
+        Api(u8, ApiRoute),
+
+        #[route("/articles/article-:numéro")]
+        Article { numéro: u32 },
+
+        #[route("/time/12:30")]
+        Time,
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum ApiRoute {
+        #[route("/users/:.json")]
+        User(String),
+    }
+
+    assert_eq!(
+        Route::parse("/files/file-12.tar.gz", true),
+        Some((
+            Route::File {
+                id: 12,
+                extension: String::from("tar.gz"),
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(Route::parse("/files/file-twelve.json", true), None);
+    assert_eq!(
+        Route::parse("/api/v2/users/J%C3%A9r%C3%B4me.json", true),
+        Some((
+            Route::Api(2, ApiRoute::User(String::from("Jérôme"))),
+            vec![]
+        ))
+    );
+    assert_eq!(Route::parse("/api/vx/users/a.json", true), None);
+    assert_eq!(
+        Route::try_parse("/files/file-.json", true),
+        Err(ParseError::NotFound)
+    );
+    assert_eq!(
+        Route::parse("/articles/article-3", true),
+        Some((Route::Article { numéro: 3 }, vec![]))
+    );
+
+    assert_eq!(
+        Route::File {
+            id: 12,
+            extension: String::from("json"),
+        }
+        .generate_url(None, true),
+        Some(String::from("/files/file-12.json"))
+    );
+    assert_eq!(
+        Route::Api(2, ApiRoute::User(String::from("Jérôme"))).generate_url(None, true),
+        Some(String::from("/api/v2/users/J%C3%A9r%C3%B4me.json"))
+    );
+    // An empty url parameter of a mixed segment wouldn't be parsed back.
+    assert_eq!(
+        Route::Api(2, ApiRoute::User(String::new())).try_generate_url(None, true),
+        Err(GenerateError::EmptyParam {
+            variants: vec![
+                RouteVariant {
+                    enum_name: "Route",
+                    variant: "Api",
+                },
+                RouteVariant {
+                    enum_name: "ApiRoute",
+                    variant: "User",
+                },
+            ],
+            field: "0",
+        })
+    );
+    assert_eq!(
+        Route::Time.generate_url(None, true),
+        Some(String::from("/time/12:30"))
+    );
+    assert_eq!(
+        Route::parse("/time/12:30", true),
+        Some((Route::Time, vec![]))
+    );
+}
+
+#[test]
+fn test_derive_trailing_slash() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(trailing_slash = "ignore")]
+    enum Route {
+        #[route("/")]
+        Home,
+
+        #[route("/about")]
+        About,
+
+        #[route("/docs/")]
+        Docs,
+
+        #[route("/blog/..")]
+        Blog(BlogRoute),
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(trailing_slash = "redirect")]
+    enum BlogRoute {
+        #[route("/")]
+        Index,
+
+        #[route("/post/:/")]
+        Post(String),
+    }
+
+    assert_eq!(Route::parse("/", true), Some((Route::Home, vec![])));
+    assert_eq!(Route::parse("/about", true), Some((Route::About, vec![])));
+    assert_eq!(Route::parse("/about/", true), Some((Route::About, vec![])));
+    assert_eq!(Route::parse("/docs", true), Some((Route::Docs, vec![])));
+    assert_eq!(
+        Route::About.generate_url(None, true),
+        Some(String::from("/about"))
+    );
+    assert_eq!(
+        Route::Docs.generate_url(None, true),
+        Some(String::from("/docs/"))
+    );
+
+    // The policy of the sub-route type applies to the end of the url
+    assert_eq!(
+        Route::parse("/blog/post/hello/", true),
+        Some((Route::Blog(BlogRoute::Post(String::from("hello"))), vec![]))
+    );
+    assert_eq!(Route::parse("/blog/post/hello", true), None);
+    assert_eq!(
+        Route::try_parse("/blog/post/hello", true),
+        Err(ParseError::TrailingSlashMismatch)
+    );
+    assert_eq!(
+        Route::parse_or_redirect("/blog/post/hello?page=2", true),
+        Some(ParseOrRedirect::Redirect(String::from(
+            "/blog/post/hello/?page=2"
+        )))
+    );
+    assert_eq!(
+        Route::parse_or_redirect("/blog/post/hello/", true),
+        Some(ParseOrRedirect::Route(
+            Route::Blog(BlogRoute::Post(String::from("hello"))),
+            vec![]
+        ))
+    );
+    assert_eq!(Route::parse_or_redirect("/blog/post", true), None);
+    assert_eq!(
+        BlogRoute::parse_or_redirect("/post/hello", true),
+        Some(ParseOrRedirect::Redirect(String::from("/post/hello/")))
+    );
+}
+
+#[test]
+fn test_derive_case_insensitive() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(case_insensitive)]
+    enum Route {
+        #[route(en => "/about")]
+        #[route(fr => "/a-propos")]
+        About,
+
+        #[route("/team/:/..")]
+        Team(String, TeamRoute),
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum TeamRoute {
+        #[route("/members")]
+        Members,
+    }
+
+    assert_eq!(
+        Route::parse("/about", true),
+        Some((Route::About, vec!["en"]))
+    );
+    assert_eq!(
+        Route::parse("/About", true),
+        Some((Route::About, vec!["en"]))
+    );
+    assert_eq!(
+        Route::parse("/A-PROPOS", true),
+        Some((Route::About, vec!["fr"]))
+    );
+    assert_eq!(
+        Route::About.generate_url(Some("fr"), true),
+        Some(String::from("/a-propos"))
+    );
+
+    // Url parameters keep their case, and the sub-route type is case-sensitive
+    assert_eq!(
+        Route::parse("/TEAM/Rust/members", true),
+        Some((
+            Route::Team(String::from("Rust"), TeamRoute::Members),
+            vec![]
+        ))
+    );
+    assert_eq!(Route::parse("/team/Rust/Members", true), None);
+
+    assert_eq!(
+        Route::parse_or_redirect("/about?ref=newsletter", true),
+        Some(ParseOrRedirect::Route(Route::About, vec!["en"]))
+    );
+    assert_eq!(
+        Route::parse_or_redirect("/A-Propos?ref=newsletter", true),
+        Some(ParseOrRedirect::Redirect(String::from(
+            "/a-propos?ref=newsletter"
+        )))
+    );
+    assert_eq!(
+        Route::parse_or_redirect("/Team/Rust/members", true),
+        Some(ParseOrRedirect::Redirect(String::from(
+            "/team/Rust/members"
+        )))
+    );
+}
+
+#[test]
+fn test_derive_canonicalize() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(trailing_slash = "redirect", case_insensitive)]
+    enum Route {
+        #[route(en => "/hello/:name/")]
+        #[route(fr => "/bonjour/:name/")]
+        Hello {
+            name: String,
+            #[query_param(optional)]
+            age: Option<u8>,
+            #[query_param(default = 1)]
+            page: u32,
+        },
+
+        #[route("/files/*path")]
+        Files { path: String },
+    }
+
+    fn canonicalize(url: &str) -> Option<(String, bool)> {
+        Route::canonicalize(url).map(|canonical| (canonical.url, canonical.changed))
+    }
+
+    assert_eq!(
+        canonicalize("/hello/John%20Doe/?age=30&page=2"),
+        Some((String::from("/hello/John%20Doe/?age=30&page=2"), false))
+    );
+    assert_eq!(
+        canonicalize("/bonjour/J%c3%a9r%c3%b4me/"),
+        Some((String::from("/bonjour/J%C3%A9r%C3%B4me/"), true))
+    );
+    assert_eq!(
+        canonicalize("/hello/John Doe/"),
+        Some((String::from("/hello/John%20Doe/"), true))
+    );
+    assert_eq!(
+        canonicalize("/hello/John/?page=2&age=30"),
+        Some((String::from("/hello/John/?age=30&page=2"), true))
+    );
+    assert_eq!(
+        canonicalize("/hello/John/?page=1&utm_source=newsletter"),
+        Some((String::from("/hello/John/"), true))
+    );
+    assert_eq!(
+        canonicalize("//Hello//John?age=30"),
+        Some((String::from("/hello/John/?age=30"), true))
+    );
+    assert_eq!(
+        canonicalize("/files//css//style.css"),
+        Some((String::from("/files/css/style.css"), true))
+    );
+    assert_eq!(canonicalize("/hello/John/?age=old"), None);
+    assert_eq!(canonicalize("/goodbye/John/"), None);
+
+    // A single redirection fixes both the trailing slash and the case of the static segments
+    assert_eq!(
+        Route::parse_or_redirect("/HELLO/John?age=30", true),
+        Some(ParseOrRedirect::Redirect(String::from(
+            "/hello/John/?age=30"
+        )))
+    );
+    assert_eq!(
+        Route::parse_or_redirect("/Bonjour/Jean", true),
+        Some(ParseOrRedirect::Redirect(String::from("/bonjour/Jean/")))
+    );
+}
+
+#[test]
+fn test_derive_multiple_query_params() {
+    use std::collections::HashSet;
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route("/posts")]
+        Posts {
+            #[query_param(multiple)]
+            tag: Vec<String>,
+            #[query_param(multiple, separator = ",")]
+            authors: HashSet<u32>,
+        },
+    }
+
+    assert_eq!(
+        Route::parse("/posts?tag=rust&tag=web%20dev&authors=1,2&authors=3", true),
+        Some((
+            Route::Posts {
+                tag: vec![String::from("rust"), String::from("web dev")],
+                authors: HashSet::from([1, 2, 3]),
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(
+        Route::parse("/posts", true),
+        Some((
+            Route::Posts {
+                tag: vec![],
+                authors: HashSet::new(),
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(
+        Route::try_parse("/posts?authors=1,two", true),
+        Err(ParseError::InvalidField {
+            variants: vec![RouteVariant {
+                enum_name: "Route",
+                variant: "Posts",
+            }],
+            field: "authors",
+            kind: FieldErrorKind::InvalidQueryParam(String::from("invalid digit found in string")),
+        })
+    );
+
+    assert_eq!(
+        Route::Posts {
+            tag: vec![String::from("rust"), String::from("web dev")],
+            authors: HashSet::from([1]),
+        }
+        .generate_url(None, true),
+        Some(String::from("/posts?tag=rust&tag=web+dev&authors=1"))
+    );
+    assert_eq!(
+        Route::Posts {
+            tag: vec![],
+            authors: HashSet::new(),
+        }
+        .generate_url(None, true),
+        Some(String::from("/posts"))
+    );
+}
+
+#[test]
+fn test_derive_renamed_query_params() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route("/posts/..sub_route")]
+        Posts {
+            sub_route: PostsRoute,
+            #[query_param(
+                default = 20,
+                rename = "pageSize",
+                alias = "per_page",
+                alias = "per-page",
+                alias = "page{size}"
+            )]
+            page_size: u32,
+        },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum PostsRoute {
+        #[route("/list")]
+        List {
+            #[query_param(multiple, rename = "t", alias = "tag")]
+            tags: Vec<String>,
+        },
+
+        #[route("/post/:id")]
+        Post {
+            id: u32,
+            #[query_param(optional, rename = "ref")]
+            referrer: Option<String>,
+        },
+    }
+
+    let route = |page_size, tags: &[&str]| Route::Posts {
+        sub_route: PostsRoute::List {
+            tags: tags.iter().map(|tag| String::from(*tag)).collect(),
+        },
+        page_size,
+    };
+
+    assert_eq!(
+        Route::parse("/posts/list?pageSize=10&t=a&tag=b", true),
+        Some((route(10, &["a", "b"]), vec![]))
+    );
+    assert_eq!(
+        Route::parse("/posts/list?per-page=30", true),
+        Some((route(30, &[]), vec![]))
+    );
+    // The primary key takes precedence over the aliases.
+    assert_eq!(
+        Route::parse("/posts/list?per_page=30&pageSize=10", true),
+        Some((route(10, &[]), vec![]))
+    );
+    assert_eq!(
+        Route::parse("/posts/list?page%7Bsize%7D=30", true),
+        Some((route(30, &[]), vec![]))
+    );
+    assert_eq!(
+        Route::parse("/posts/post/1?ref=home", true),
+        Some((
+            Route::Posts {
+                sub_route: PostsRoute::Post {
+                    id: 1,
+                    referrer: Some(String::from("home")),
+                },
+                page_size: 20,
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(
+        Route::try_parse("/posts/list?per_page=ten", true),
+        Err(ParseError::InvalidField {
+            variants: vec![RouteVariant {
+                enum_name: "Route",
+                variant: "Posts",
+            }],
+            field: "page_size",
+            kind: FieldErrorKind::InvalidQueryParam(String::from("invalid digit found in string")),
+        })
+    );
+
+    assert_eq!(
+        route(10, &["a", "b"]).generate_url(None, true),
+        Some(String::from("/posts/list?pageSize=10&t=a&t=b"))
+    );
+}
+
+#[test]
+fn test_derive_query_prefix() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(unknown_query = "reject")]
+    enum Route {
+        #[route("/users/..list", query_prefix = "list.")]
+        Users {
+            list: ListRoute,
+            #[query_param(default = 1)]
+            page: u32,
+        },
+
+        #[route("/compare/..left", query_prefix = "left.")]
+        Compare { left: CompareRoute },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum CompareRoute {
+        #[route("/with/..right", query_prefix = "right.")]
+        With {
+            right: ListRoute,
+            #[query_param(default = 1)]
+            page: u32,
+        },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum ListRoute {
+        #[route("/list")]
+        List {
+            #[query_param(default = 1)]
+            page: u32,
+            #[query_param(optional)]
+            sort: Option<String>,
+        },
+    }
+
+    let list = |page, sort: Option<&str>| ListRoute::List {
+        page,
+        sort: sort.map(String::from),
+    };
+    let compare = |left_page, right_page, right_sort| Route::Compare {
+        left: CompareRoute::With {
+            right: list(right_page, right_sort),
+            page: left_page,
+        },
+    };
+
+    assert_eq!(
+        Route::parse("/users/list?page=2&list.page=3&list.sort=name", true),
+        Some((
+            Route::Users {
+                list: list(3, Some("name")),
+                page: 2,
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(
+        Route::parse("/users/list?list.page=3", true),
+        Some((
+            Route::Users {
+                list: list(3, None),
+                page: 1,
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(
+        Route::parse("/compare/with/list?left.page=2&left.right.page=3", true),
+        Some((compare(2, 3, None), vec![]))
+    );
+    // The keys of the sub-routes are only known with their prefixes.
+    assert_eq!(
+        Route::try_parse("/users/list?sort=name", true),
+        Err(ParseError::UnknownQueryParam(String::from("sort")))
+    );
+    assert_eq!(
+        Route::try_parse("/compare/with/list?right.page=3", true),
+        Err(ParseError::UnknownQueryParam(String::from("right.page")))
+    );
+
+    assert_eq!(
+        Route::Users {
+            list: list(3, Some("name")),
+            page: 2,
+        }
+        .generate_url(None, true),
+        Some(String::from(
+            "/users/list?page=2&list.page=3&list.sort=name"
+        ))
+    );
+    assert_eq!(
+        compare(2, 3, Some("a b")).generate_url(None, true),
+        Some(String::from(
+            "/compare/with/list?left.page=2&left.right.page=3&left.right.sort=a+b"
+        ))
+    );
+
+    assert!(route_tree_uses_query_key(Route::ROUTES, "list.sort"));
+    assert!(route_tree_uses_query_key(Route::ROUTES, "left.right.page"));
+    assert!(!route_tree_uses_query_key(Route::ROUTES, "sort"));
+    assert!(!route_tree_uses_query_key(Route::ROUTES, "right.page"));
+}
+
+#[test]
+fn test_derive_query_order() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum TreeRoute {
+        #[route("/posts/..sub_route")]
+        Posts {
+            #[query_param(default = 1)]
+            page: u32,
+            sub_route: PostsRoute,
+            #[query_param(optional)]
+            lang: Option<String>,
+        },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(query_order = "declaration")]
+    enum DeclarationRoute {
+        #[route("/posts/..sub_route")]
+        Posts {
+            #[query_param(default = 1)]
+            page: u32,
+            sub_route: PostsRoute,
+            #[query_param(optional)]
+            lang: Option<String>,
+        },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(query_order = "alphabetical")]
+    enum AlphabeticalRoute {
+        #[route("/posts/..sub_route")]
+        Posts {
+            #[query_param(default = 1)]
+            page: u32,
+            sub_route: PostsRoute,
+            #[query_param(optional)]
+            lang: Option<String>,
+        },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum PostsRoute {
+        #[route("/list")]
+        List {
+            #[query_param(optional)]
+            sort: Option<String>,
+            #[query_param(multiple)]
+            tag: Vec<String>,
+        },
+    }
+
+    let posts_route = || PostsRoute::List {
+        sort: Some(String::from("date")),
+        tag: vec![String::from("b"), String::from("a")],
+    };
+    let lang = || Some(String::from("fr"));
+    // The query string parameters are shuffled, so that the generated urls don't depend on the order of the parsed ones.
+    let url = "/posts/list?tag=b&lang=fr&tag=a&sort=date&page=2";
+
+    let tree_url = "/posts/list?page=2&lang=fr&sort=date&tag=b&tag=a";
+    let tree_route = || TreeRoute::Posts {
+        page: 2,
+        sub_route: posts_route(),
+        lang: lang(),
+    };
+    assert_eq!(
+        tree_route().generate_url(None, true).as_deref(),
+        Some(tree_url)
+    );
+    assert_eq!(
+        TreeRoute::parse(url, true).and_then(|(route, _)| route.generate_url(None, true)),
+        Some(String::from(tree_url))
+    );
+
+    let declaration_url = "/posts/list?page=2&sort=date&tag=b&tag=a&lang=fr";
+    let declaration_route = || DeclarationRoute::Posts {
+        page: 2,
+        sub_route: posts_route(),
+        lang: lang(),
+    };
+    assert_eq!(
+        declaration_route().generate_url(None, true).as_deref(),
+        Some(declaration_url)
+    );
+    assert_eq!(
+        DeclarationRoute::parse(url, true).and_then(|(route, _)| route.generate_url(None, true)),
+        Some(String::from(declaration_url))
+    );
+    // The query string parameters of the sub-route are at the position of its field, even if the route has none before it.
+    assert_eq!(
+        DeclarationRoute::Posts {
+            page: 1,
+            sub_route: posts_route(),
+            lang: lang(),
+        }
+        .generate_url(None, true),
+        Some(String::from("/posts/list?sort=date&tag=b&tag=a&lang=fr"))
+    );
+
+    // The values of a repeated query string parameter are sorted too.
+    let alphabetical_url = "/posts/list?lang=fr&page=2&sort=date&tag=a&tag=b";
+    let alphabetical_route = || AlphabeticalRoute::Posts {
+        page: 2,
+        sub_route: posts_route(),
+        lang: lang(),
+    };
+    assert_eq!(
+        alphabetical_route().generate_url(None, true).as_deref(),
+        Some(alphabetical_url)
+    );
+    assert_eq!(
+        AlphabeticalRoute::parse(url, true).and_then(|(route, _)| route.generate_url(None, true)),
+        Some(String::from(alphabetical_url))
+    );
+    assert_eq!(
+        AlphabeticalRoute::canonicalize(url).map(|canonical| canonical.url),
+        Some(String::from(alphabetical_url))
+    );
+}
+
+#[test]
+fn test_derive_alphabetical_query_order_with_refactored_tree() {
+    use std::collections::HashSet;
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(query_order = "alphabetical")]
+    enum NestedRoute {
+        #[route("/posts/..sub_route")]
+        Posts {
+            #[query_param(optional)]
+            lang: Option<String>,
+            sub_route: PostsRoute,
+        },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum PostsRoute {
+        #[route("/list")]
+        List {
+            #[query_param(multiple)]
+            tag: HashSet<String>,
+            #[query_param(multiple, separator = ",")]
+            authors: HashSet<u32>,
+        },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(query_order = "alphabetical")]
+    enum FlatRoute {
+        #[route("/posts/list")]
+        PostsList {
+            #[query_param(multiple, separator = ",")]
+            authors: Vec<u32>,
+            #[query_param(multiple)]
+            tag: Vec<String>,
+            #[query_param(optional)]
+            lang: Option<String>,
+        },
+    }
+
+    let tags = ["rust", "web", "async", "cli", "wasm", "db"];
+    let authors = [12, 3, 7, 45, 1, 28];
+    let nested_route = NestedRoute::Posts {
+        lang: Some(String::from("fr")),
+        sub_route: PostsRoute::List {
+            tag: tags.iter().copied().map(String::from).collect(),
+            authors: authors.iter().copied().collect(),
+        },
+    };
+    let flat_route = FlatRoute::PostsList {
+        authors: authors.iter().rev().copied().collect(),
+        tag: tags.iter().copied().map(String::from).collect(),
+        lang: Some(String::from("fr")),
+    };
+
+    let url = "/posts/list?authors=1,12,28,3,45,7&lang=fr&tag=async&tag=cli&tag=db&tag=rust&tag=wasm&tag=web";
+    assert_eq!(nested_route.generate_url(None, true).as_deref(), Some(url));
+    assert_eq!(flat_route.generate_url(None, true).as_deref(), Some(url));
+    assert_eq!(NestedRoute::parse(url, true), Some((nested_route, vec![])));
+}
+
+#[test]
+fn test_derive_query_flags() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(query_order = "alphabetical")]
+    enum Route {
+        #[route("/admin/..sub_route", query_prefix = "admin.")]
+        Admin {
+            sub_route: AdminRoute,
+            #[query_param(flag)]
+            debug: bool,
+        },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum AdminRoute {
+        #[route("/users")]
+        Users {
+            #[query_param(flag, rename = "all", alias = "a")]
+            show_all: bool,
+            #[query_param(optional)]
+            search: Option<String>,
+        },
+    }
+
+    let route = |debug, show_all, search: Option<&str>| Route::Admin {
+        sub_route: AdminRoute::Users {
+            show_all,
+            search: search.map(String::from),
+        },
+        debug,
+    };
+
+    assert_eq!(
+        Route::parse("/admin/users", true),
+        Some((route(false, false, None), vec![]))
+    );
+    assert_eq!(
+        Route::parse("/admin/users?debug&admin.all", true),
+        Some((route(true, true, None), vec![]))
+    );
+    assert_eq!(
+        Route::parse("/admin/users?debug=&admin.a=true&admin.search=", true),
+        Some((route(true, true, Some("")), vec![]))
+    );
+    assert_eq!(
+        Route::parse("/admin/users?debug=false", true),
+        Some((route(true, false, None), vec![]))
+    );
+    assert_eq!(
+        Route::parse("/admin/users?debug=1&admin.a=0", true),
+        Some((route(true, true, None), vec![]))
+    );
+    assert_eq!(
+        Route::parse("/admin/users?debug=yes", true),
+        Some((route(true, false, None), vec![]))
+    );
+    assert_eq!(
+        Route::canonicalize("/admin/users?debug=1").map(|canonical| canonical.url),
+        Some(String::from("/admin/users?debug"))
+    );
+
+    assert_eq!(
+        route(false, false, None).generate_url(None, true),
+        Some(String::from("/admin/users"))
+    );
+    assert_eq!(
+        route(true, true, Some("")).generate_url(None, true),
+        Some(String::from("/admin/users?admin.all&admin.search=&debug"))
+    );
+    assert_eq!(
+        route(true, false, Some("a b")).generate_url(None, true),
+        Some(String::from("/admin/users?admin.search=a+b&debug"))
+    );
+}
+
+#[test]
+fn test_derive_unknown_query() {
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(unknown_query = "capture")]
+    enum Route {
+        #[route("/")]
+        Home,
+
+        #[route("/posts/..sub_route")]
+        Posts {
+            sub_route: PostsRoute,
+            #[query_param(default = 1)]
+            page: u32,
+        },
+
+        #[route("/drafts/..sub_route", query_prefix = "draft.")]
+        Drafts { sub_route: PostsRoute },
+
+        #[route("/archives/..sub_route", query_prefix = "archive.")]
+        Archives {
+            sub_route: PostsRoute,
+            #[query_rest]
+            extra: Vec<(String, Option<String>)>,
+        },
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(unknown_query = "reject")]
+    enum PostsRoute {
+        #[route("/:id")]
+        Post {
+            id: u32,
+            #[query_param(optional, alias = "ref")]
+            referrer: Option<String>,
+            #[query_rest]
+            extra: Vec<(String, Option<String>)>,
+        },
+    }
+
+    let post = |extra: &[(&str, Option<&str>)]| Route::Posts {
+        sub_route: PostsRoute::Post {
+            id: 1,
+            referrer: Some(String::from("home")),
+            extra: extra
+                .iter()
+                .map(|(key, value)| (String::from(*key), value.map(String::from)))
+                .collect(),
+        },
+        page: 2,
+    };
+
+    // The keys of every level of the route are claimed.
+    assert_eq!(
+        Route::parse(
+            "/posts/1?utm_source=mail&page=2&ref=home&utm_medium=a%20b",
+            true
+        ),
+        Some((
+            post(&[("utm_source", Some("mail")), ("utm_medium", Some("a b"))]),
+            vec![]
+        ))
+    );
+    assert_eq!(
+        post(&[("utm_source", Some("mail")), ("utm_medium", Some("a b"))]).generate_url(None, true),
+        Some(String::from(
+            "/posts/1?page=2&referrer=home&utm_source=mail&utm_medium=a+b"
+        ))
+    );
+
+    // The query string parameters without a value are generated again without a value.
+    assert_eq!(
+        Route::parse("/posts/1?page=2&ref=home&utm&utm_id=", true),
+        Some((post(&[("utm", None), ("utm_id", Some(""))]), vec![]))
+    );
+    assert_eq!(
+        Route::canonicalize("/posts/1?page=2&referrer=home&utm&utm_id="),
+        Some(Canonical {
+            url: String::from("/posts/1?page=2&referrer=home&utm&utm_id="),
+            changed: false,
+        })
+    );
+    assert_eq!(
+        Route::parse("/?utm_source=mail", true),
+        Some((Route::Home, vec![]))
+    );
+
+    // Below a query prefix, only the unclaimed query string parameters starting with it are collected, without it.
+    let draft = Route::Drafts {
+        sub_route: PostsRoute::Post {
+            id: 1,
+            referrer: Some(String::from("home")),
+            extra: vec![(String::from("utm_source"), Some(String::from("mail")))],
+        },
+    };
+    assert_eq!(
+        draft.generate_url(None, true),
+        Some(String::from(
+            "/drafts/1?draft.referrer=home&draft.utm_source=mail"
+        ))
+    );
+    assert_eq!(
+        Route::parse(
+            "/drafts/1?draft.ref=home&draft.utm_source=mail&utm_medium=web",
+            true
+        ),
+        Some((draft, vec![]))
+    );
+
+    // Above the query prefix, all of them are collected.
+    let archive = Route::Archives {
+        sub_route: PostsRoute::Post {
+            id: 1,
+            referrer: Some(String::from("home")),
+            extra: vec![],
+        },
+        extra: vec![
+            (
+                String::from("archive.utm_source"),
+                Some(String::from("mail")),
+            ),
+            (String::from("utm_medium"), Some(String::from("web"))),
+        ],
+    };
+    assert_eq!(
+        Route::parse(
+            "/archives/1?archive.ref=home&archive.utm_source=mail&utm_medium=web",
+            true
+        ),
+        Some((archive, vec![]))
+    );
+
+    // The policy of the outermost type applies.
+    assert_eq!(
+        PostsRoute::try_parse("/1?referrer=home&utm_source=mail", true),
+        Err(ParseError::UnknownQueryParam(String::from("utm_source")))
+    );
+    assert_eq!(
+        PostsRoute::parse("/1?ref=home", true),
+        Some((
+            PostsRoute::Post {
+                id: 1,
+                referrer: Some(String::from("home")),
+                extra: vec![],
+            },
+            vec![]
+        ))
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_derive_flattened_query_params() {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug)]
+    #[serde(default)]
+    struct Filter {
+        price: Price,
+        brands: Vec<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug)]
+    struct Price {
+        min: Option<u32>,
+        max: Option<u32>,
+    }
+
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    enum Route {
+        #[route("/search")]
+        Search {
+            #[query_param(default = 1)]
+            page: u32,
+            #[query_param(flatten)]
+            filter: Filter,
+        },
+
+        // Only structs and maps can be flattened.
+        #[route("/zoom")]
+        Zoom {
+            #[query_param(flatten)]
+            level: u32,
+        },
+    }
+
+    let route = || Route::Search {
+        page: 2,
+        filter: Filter {
+            price: Price {
+                min: Some(10),
+                max: None,
+            },
+            brands: vec![String::from("a b"), String::from("c")],
+        },
+    };
+
+    assert_eq!(
+        Route::parse(
+            "/search?page=2&price[min]=10&brands[0]=a+b&brands[1]=c",
+            true
+        ),
+        Some((route(), vec![]))
+    );
+    assert_eq!(
+        Route::parse(
+            "/search?price%5Bmin%5D=10&brands[]=a%20b&brands[]=c&page=2",
+            true
+        ),
+        Some((route(), vec![]))
+    );
+    assert_eq!(
+        Route::parse("/search", true),
+        Some((
+            Route::Search {
+                page: 1,
+                filter: Filter::default(),
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(
+        Route::try_parse("/search?price[min]=ten", true),
+        Err(ParseError::InvalidField {
+            variants: vec![RouteVariant {
+                enum_name: "Route",
+                variant: "Search",
+            }],
+            field: "filter",
+            kind: FieldErrorKind::InvalidQueryParam(String::from("invalid digit found in string")),
+        })
+    );
+
+    let url = route().generate_url(None, true).unwrap();
+    assert_eq!(
+        url,
+        "/search?page=2&price[min]=10&brands[0]=a+b&brands[1]=c"
+    );
+    assert_eq!(Route::parse(&url, true), Some((route(), vec![])));
+
+    // Only the keys of the fields of the flattened type are claimed
+    #[derive(Chemin, PartialEq, Eq, Debug)]
+    #[chemin(unknown_query = "reject")]
+    enum StrictRoute {
+        #[route("/search")]
+        Search {
+            #[query_param(flatten)]
+            filter: Filter,
+        },
+    }
+
+    assert_eq!(
+        StrictRoute::parse("/search?price[min]=10&brands[]=a", true),
+        Some((
+            StrictRoute::Search {
+                filter: Filter {
+                    price: Price {
+                        min: Some(10),
+                        max: None,
+                    },
+                    brands: vec![String::from("a")],
+                },
+            },
+            vec![]
+        ))
+    );
+    assert_eq!(
+        StrictRoute::try_parse("/search?price[min]=10&utm_source=newsletter", true),
+        Err(ParseError::UnknownQueryParam(String::from("utm_source")))
+    );
+
+    // The message of the serializer isn't checked, since it is defined by serde_qs.
+    assert!(matches!(
+        Route::Zoom { level: 2 }.try_generate_url(None, true),
+        Err(GenerateError::InvalidQueryParams { variants, field: "level", .. })
+            if variants == [RouteVariant { enum_name: "Route", variant: "Zoom" }]
+    ));
+}
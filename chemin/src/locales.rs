@@ -0,0 +1,239 @@
+use crate::Locale;
+
+/// A policy choosing which other locales to try when a route isn't defined for the requested locale. It is used by
+/// [Chemin::generate_url_with_fallback](crate::Chemin::generate_url_with_fallback).
+///
+/// The locales are tried in this order:
+/// 1. The requested locale.
+/// 2. If `truncate` is `true`, the requested locale truncated one subtag at a time, as in the BCP-47 "lookup" matching scheme
+///    (for example, `"zh-Hant-TW"`, then `"zh-Hant"`, then `"zh"`).
+/// 3. The locales of `default_locales`, in order.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct LocaleFallback {
+    /// Whether to try the truncations of the requested locale.
+    pub truncate: bool,
+    /// The locales to try when neither the requested locale nor its truncations are available.
+    pub default_locales: Vec<String>,
+}
+
+impl LocaleFallback {
+    /// A policy which only tries the truncations of the requested locale.
+    pub fn new() -> Self {
+        Self {
+            truncate: true,
+            default_locales: Vec::new(),
+        }
+    }
+
+    /// A policy which tries the truncations of the requested locale, and then the provided `default_locales`.
+    pub fn with_default_locales(default_locales: &[&str]) -> Self {
+        Self {
+            truncate: true,
+            default_locales: default_locales
+                .iter()
+                .map(|locale| String::from(*locale))
+                .collect(),
+        }
+    }
+
+    /// Returns the locales to try, in order, without duplicates. The first one is always the requested `locale`.
+    pub fn candidates<'a>(&'a self, locale: Option<&'a str>) -> Vec<Option<&'a str>> {
+        let mut candidates = vec![locale];
+
+        if let (true, Some(mut locale)) = (self.truncate, locale) {
+            while let Some(truncated_locale) = truncate_locale(locale) {
+                candidates.push(Some(truncated_locale));
+                locale = truncated_locale;
+            }
+        }
+
+        for default_locale in &self.default_locales {
+            if !candidates.contains(&Some(default_locale)) {
+                candidates.push(Some(default_locale));
+            }
+        }
+
+        candidates
+    }
+}
+
+impl Default for LocaleFallback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Chooses the best locale among the `available` ones, according to an HTTP
+/// [`Accept-Language`](https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Accept-Language) header.
+///
+/// The language ranges of the header are considered by decreasing quality value. For each of them, an available locale is looked
+/// for in this order: a locale equal to the language range (case-insensitively), then a more specific locale (`"fr"` matches
+/// `"fr-FR"`), then a truncation of the language range (`"fr-CH"` matches `"fr"`). The wildcard `"*"` matches the first available
+/// locale.
+///
+/// Returns [None] if no available locale is acceptable.
+///
+/// ```
+/// use chemin::{negotiate_locale, Chemin};
+///
+/// ##[derive(Chemin)]
+/// enum Route {
+///     ##[route(en => "/about")]
+///     ##[route(fr => "/a-propos")]
+///     About,
+/// }
+///
+/// let available_locales = Route::About.available_locales();
+/// let locale = negotiate_locale("fr-CH, fr;q=0.9, en;q=0.8", &available_locales);
+/// assert_eq!(locale, Some("fr"));
+/// assert_eq!(Route::About.generate_url(locale, true), Some(String::from("/a-propos")));
+/// ```
+pub fn negotiate_locale(accept_language: &str, available: &[Locale]) -> Option<Locale> {
+    let mut language_ranges: Vec<(&str, f32)> = accept_language
+        .split(',')
+        .filter_map(parse_language_range)
+        .filter(|(_, quality)| *quality > 0.0)
+        .collect();
+
+    // The sort is stable, so language ranges of equal quality keep the order of the header.
+    language_ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+    for (language_range, _) in language_ranges {
+        if language_range == "*" {
+            return available.first().copied();
+        }
+
+        let exact_match = |language_range: &str| {
+            available
+                .iter()
+                .copied()
+                .find(|locale| locale.eq_ignore_ascii_case(language_range))
+        };
+
+        if let Some(locale) = exact_match(language_range) {
+            return Some(locale);
+        }
+
+        if let Some(locale) = available.iter().copied().find(|locale| {
+            locale.len() > language_range.len()
+                && locale.as_bytes()[language_range.len()] == b'-'
+                && locale[..language_range.len()].eq_ignore_ascii_case(language_range)
+        }) {
+            return Some(locale);
+        }
+
+        let mut truncated_language_range = language_range;
+
+        while let Some(value) = truncate_locale(truncated_language_range) {
+            if let Some(locale) = exact_match(value) {
+                return Some(locale);
+            }
+
+            truncated_language_range = value;
+        }
+    }
+
+    None
+}
+
+/// Parses an item of an `Accept-Language` header, such as `"fr-CH"` or `"en;q=0.8"`, into a language range and its quality value.
+fn parse_language_range(item: &str) -> Option<(&str, f32)> {
+    let mut parts = item.split(';');
+    let language_range = parts.next()?.trim();
+
+    if language_range.is_empty() || !language_range.is_ascii() {
+        return None;
+    }
+
+    let mut quality = 1.0;
+
+    for param in parts {
+        let (key, value) = param.split_once('=')?;
+
+        if key.trim() == "q" {
+            quality = value.trim().parse().ok()?;
+        }
+    }
+
+    Some((language_range, quality))
+}
+
+/// Removes the last subtag of a locale, and the single-character subtag (such as the "x" of private use subtags) which could then
+/// end it.
+fn truncate_locale(locale: &str) -> Option<&str> {
+    let mut truncated_locale = &locale[..locale.rfind('-')?];
+
+    if let Some(i) = truncated_locale.rfind('-') {
+        if truncated_locale.len() - i == 2 {
+            truncated_locale = &truncated_locale[..i];
+        }
+    }
+
+    Some(truncated_locale)
+}
+
+#[test]
+fn test_locale_fallback_candidates() {
+    assert_eq!(LocaleFallback::new().candidates(None), vec![None]);
+    assert_eq!(
+        LocaleFallback::new().candidates(Some("fr")),
+        vec![Some("fr")]
+    );
+    assert_eq!(
+        LocaleFallback::new().candidates(Some("zh-Hant-TW")),
+        vec![Some("zh-Hant-TW"), Some("zh-Hant"), Some("zh")],
+    );
+    assert_eq!(
+        LocaleFallback::new().candidates(Some("de-CH-x-phonebk")),
+        vec![Some("de-CH-x-phonebk"), Some("de-CH"), Some("de")],
+    );
+
+    let fallback = LocaleFallback::with_default_locales(&["en", "fr"]);
+    assert_eq!(
+        fallback.candidates(None),
+        vec![None, Some("en"), Some("fr")]
+    );
+    assert_eq!(
+        fallback.candidates(Some("fr-CA")),
+        vec![Some("fr-CA"), Some("fr"), Some("en")],
+    );
+
+    let fallback = LocaleFallback {
+        truncate: false,
+        default_locales: vec![String::from("en")],
+    };
+    assert_eq!(
+        fallback.candidates(Some("fr-CA")),
+        vec![Some("fr-CA"), Some("en")],
+    );
+}
+
+#[test]
+fn test_negotiate_locale() {
+    let available = ["en", "en-US", "fr-FR"];
+
+    assert_eq!(negotiate_locale("en-US", &available), Some("en-US"));
+    assert_eq!(negotiate_locale("EN-us", &available), Some("en-US"));
+    assert_eq!(negotiate_locale("en-GB", &available), Some("en"));
+    assert_eq!(negotiate_locale("fr", &available), Some("fr-FR"));
+    assert_eq!(negotiate_locale("de", &available), None);
+    assert_eq!(negotiate_locale("de, *;q=0.1", &available), Some("en"));
+    assert_eq!(negotiate_locale("", &available), None);
+    assert_eq!(negotiate_locale("en", &[]), None);
+
+    // Quality values
+    assert_eq!(
+        negotiate_locale("en;q=0.5, fr-FR;q=0.9", &available),
+        Some("fr-FR")
+    );
+    assert_eq!(
+        negotiate_locale("fr-CH, fr;q=0.9, en;q=0.8", &available),
+        Some("fr-FR")
+    );
+    assert_eq!(negotiate_locale("fr;q=0, en;q=0.1", &available), Some("en"));
+    assert_eq!(
+        negotiate_locale("fr;q=invalid, en;q=0.1", &available),
+        Some("en")
+    );
+    assert_eq!(negotiate_locale("de, fr", &available), Some("fr-FR"));
+}
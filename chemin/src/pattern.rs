@@ -0,0 +1,397 @@
+use regex::Regex;
+use smallvec::SmallVec;
+use std::sync::OnceLock;
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Pattern {
+    pub components: &'static [PatternComponent],
+    pub sub_route: bool,
+    pub catch_all: bool,
+    pub trailing_slash: bool,
+    /// Whether the static segments are matched case-insensitively (ASCII only).
+    pub case_insensitive: bool,
+}
+
+#[doc(hidden)]
+#[derive(Debug)]
+pub enum PatternComponent {
+    Static(&'static str),
+    Param(Option<&'static Constraint>),
+    /// A segment mixing static text and url parameters (as in `"/file-:id.json"`), matched by a regex capturing each url
+    /// parameter in a group named `__chemin_param_{i}`.
+    Mixed(&'static Constraint),
+}
+
+/// The constraint of a url parameter (as in `"/post/:id<\\d+>"`): a regex which the whole url parameter, as it appears in the url
+/// (that is to say percent-encoded), must match.
+#[doc(hidden)]
+#[derive(Debug)]
+pub struct Constraint {
+    pub source: &'static str,
+    regex: OnceLock<Regex>,
+}
+
+impl Constraint {
+    pub const fn new(source: &'static str) -> Self {
+        Self {
+            source,
+            regex: OnceLock::new(),
+        }
+    }
+
+    pub fn matches(&self, value: &str) -> bool {
+        self.regex().is_match(value)
+    }
+
+    /// Returns the url parameters captured by the groups named `__chemin_param_{i}`, in order, or `None` if `value` doesn't match
+    /// or if one of them is empty.
+    fn capture_params<'a>(&self, value: &'a str) -> Option<SmallVec<[&'a str; 4]>> {
+        let regex = self.regex();
+        let captures = regex.captures(value)?;
+
+        regex
+            .capture_names()
+            .flatten()
+            .filter(|name| name.starts_with("__chemin_param_"))
+            .map(|name| captures.name(name).map(|param| param.as_str()))
+            .map(|param| param.filter(|param| !param.is_empty()))
+            .collect()
+    }
+
+    fn regex(&self) -> &Regex {
+        self.regex
+            .get_or_init(|| Regex::new(&format!("^(?:{})$", self.source)).unwrap())
+    }
+}
+
+#[doc(hidden)]
+#[derive(PartialEq, Eq, Debug)]
+pub struct Match<'a> {
+    /// The url parameters, in the order of the path. The rest of the path captured by the catch-all param (without its leading
+    /// slash) is the last one.
+    pub params: SmallVec<[&'a str; 4]>,
+    /// The rest of the path, to be parsed by the sub-route.
+    pub sub_route_path: Option<&'a str>,
+    /// Whether a static segment was matched with a different case than in the path of the route.
+    pub case_mismatch: bool,
+}
+
+impl Pattern {
+    pub fn matches<'a>(&self, path: &'a str) -> Option<Match<'a>> {
+        // The leading slash is optional.
+        let full_path = path;
+        let path = path.strip_prefix('/').unwrap_or(path);
+        let mut params = SmallVec::new();
+        let mut case_mismatch = false;
+        // The start of the next segment, or `None` if the end of the path was reached.
+        let mut segment_start = Some(0);
+
+        for component in self.components {
+            let start = segment_start?;
+            let (segment, next_segment_start) = match path[start..].find('/') {
+                Some(i) => (&path[start..start + i], Some(start + i + 1)),
+                None => (&path[start..], None),
+            };
+
+            match component {
+                PatternComponent::Static(value) => {
+                    if segment != *value {
+                        if self.case_insensitive && segment.eq_ignore_ascii_case(value) {
+                            case_mismatch = true;
+                        } else {
+                            return None;
+                        }
+                    }
+                }
+
+                PatternComponent::Param(constraint) => {
+                    if segment.is_empty() {
+                        return None;
+                    }
+
+                    if let Some(constraint) = constraint {
+                        if !constraint.matches(segment) {
+                            return None;
+                        }
+                    }
+
+                    params.push(segment);
+                }
+
+                PatternComponent::Mixed(constraint) => {
+                    params.extend(constraint.capture_params(segment)?);
+                }
+            }
+
+            segment_start = next_segment_start;
+        }
+
+        let sub_route_path = if self.sub_route {
+            // The rest of the path can't be empty, and it keeps its leading slash.
+            let start = segment_start?;
+
+            if start >= path.len() {
+                return None;
+            }
+
+            Some(if start == 0 {
+                full_path
+            } else {
+                &path[start - 1..]
+            })
+        } else if self.catch_all {
+            // The rest of the path can't be empty.
+            let start = segment_start?;
+
+            if start >= path.len() {
+                return None;
+            }
+
+            params.push(&path[start..]);
+            None
+        } else if self.trailing_slash {
+            let matches = if self.components.is_empty() {
+                path.is_empty()
+            } else {
+                segment_start == Some(path.len())
+            };
+
+            if !matches {
+                return None;
+            }
+
+            None
+        } else {
+            if segment_start.is_some() {
+                return None;
+            }
+
+            None
+        };
+
+        Some(Match {
+            params,
+            sub_route_path,
+            case_mismatch,
+        })
+    }
+}
+
+#[test]
+fn test_pattern_matches() {
+    use smallvec::smallvec;
+
+    fn matches(
+        pattern: &Pattern,
+        path: &'static str,
+    ) -> Option<(Vec<&'static str>, Option<&'static str>)> {
+        pattern
+            .matches(path)
+            .map(|match_| (match_.params.to_vec(), match_.sub_route_path))
+    }
+
+    let root = Pattern {
+        components: &[],
+        sub_route: false,
+        catch_all: false,
+        trailing_slash: true,
+        case_insensitive: false,
+    };
+    assert_eq!(matches(&root, "/"), Some((vec![], None)));
+    assert_eq!(matches(&root, ""), Some((vec![], None)));
+    assert_eq!(matches(&root, "/hello"), None);
+
+    let hello = Pattern {
+        components: &[
+            PatternComponent::Static("hello"),
+            PatternComponent::Param(None),
+        ],
+        sub_route: false,
+        catch_all: false,
+        trailing_slash: false,
+        case_insensitive: false,
+    };
+    assert_eq!(
+        hello.matches("/hello/john"),
+        Some(Match {
+            params: smallvec!["john"],
+            sub_route_path: None,
+            case_mismatch: false,
+        })
+    );
+    assert_eq!(matches(&hello, "/hello/john/"), None);
+    assert_eq!(matches(&hello, "/hello/"), None);
+    assert_eq!(matches(&hello, "/hello"), None);
+    assert_eq!(matches(&hello, "/bonjour/john"), None);
+    assert_eq!(matches(&hello, "/hello/john/doe"), None);
+
+    let hello_with_trailing_slash = Pattern {
+        components: &[
+            PatternComponent::Static("hello"),
+            PatternComponent::Param(None),
+        ],
+        sub_route: false,
+        catch_all: false,
+        trailing_slash: true,
+        case_insensitive: false,
+    };
+    assert_eq!(
+        matches(&hello_with_trailing_slash, "/hello/john/"),
+        Some((vec!["john"], None))
+    );
+    assert_eq!(matches(&hello_with_trailing_slash, "/hello/john"), None);
+    assert_eq!(matches(&hello_with_trailing_slash, "/hello//"), None);
+
+    let with_sub_route = Pattern {
+        components: &[PatternComponent::Param(None)],
+        sub_route: true,
+        catch_all: false,
+        trailing_slash: false,
+        case_insensitive: false,
+    };
+    assert_eq!(
+        matches(&with_sub_route, "/john/hello/world"),
+        Some((vec!["john"], Some("/hello/world")))
+    );
+    assert_eq!(
+        matches(&with_sub_route, "/john/hello/"),
+        Some((vec!["john"], Some("/hello/")))
+    );
+    assert_eq!(matches(&with_sub_route, "/john/"), None);
+    assert_eq!(matches(&with_sub_route, "/john"), None);
+
+    let only_sub_route = Pattern {
+        components: &[],
+        sub_route: true,
+        catch_all: false,
+        trailing_slash: false,
+        case_insensitive: false,
+    };
+    assert_eq!(
+        matches(&only_sub_route, "/hello"),
+        Some((vec![], Some("/hello")))
+    );
+    assert_eq!(matches(&only_sub_route, "/"), None);
+}
+
+#[test]
+fn test_pattern_matches_catch_all() {
+    let with_catch_all = Pattern {
+        components: &[PatternComponent::Static("static")],
+        sub_route: false,
+        catch_all: true,
+        trailing_slash: false,
+        case_insensitive: false,
+    };
+    assert_eq!(
+        with_catch_all
+            .matches("/static/css/style.css")
+            .unwrap()
+            .params[..],
+        ["css/style.css"]
+    );
+    assert_eq!(
+        with_catch_all.matches("/static/css/").unwrap().params[..],
+        ["css/"]
+    );
+    assert_eq!(with_catch_all.matches("/static/"), None);
+    assert_eq!(with_catch_all.matches("/static"), None);
+
+    let only_catch_all = Pattern {
+        components: &[],
+        sub_route: false,
+        catch_all: true,
+        trailing_slash: false,
+        case_insensitive: false,
+    };
+    assert_eq!(
+        only_catch_all.matches("/hello/world").unwrap().params[..],
+        ["hello/world"]
+    );
+    assert_eq!(only_catch_all.matches("/"), None);
+}
+
+#[test]
+fn test_pattern_matches_constraints() {
+    static ID: Constraint = Constraint::new("\\d+");
+
+    static POST: Pattern = Pattern {
+        components: &[
+            PatternComponent::Static("post"),
+            PatternComponent::Param(Some(&ID)),
+        ],
+        sub_route: false,
+        catch_all: false,
+        trailing_slash: false,
+        case_insensitive: false,
+    };
+    assert!(POST.matches("/post/12").is_some());
+    assert!(POST.matches("/post/new").is_none());
+    assert!(POST.matches("/post/12a").is_none());
+    assert!(POST.matches("/post/").is_none());
+
+    // The constraint applies to the whole segment, even if it contains an alternation
+    static COLOR: Constraint = Constraint::new("red|green");
+    assert!(COLOR.matches("red"));
+    assert!(!COLOR.matches("reddish"));
+    assert!(!COLOR.matches("dark-green"));
+}
+
+#[test]
+fn test_pattern_matches_mixed_segments() {
+    use smallvec::smallvec;
+
+    static FILE: Constraint =
+        Constraint::new(r"file\-(?P<__chemin_param_0>(?:\d+))\.(?P<__chemin_param_1>[^/]+?)");
+
+    static FILES: Pattern = Pattern {
+        components: &[
+            PatternComponent::Static("files"),
+            PatternComponent::Mixed(&FILE),
+        ],
+        sub_route: false,
+        catch_all: false,
+        trailing_slash: false,
+        case_insensitive: false,
+    };
+    assert_eq!(
+        FILES.matches("/files/file-12.tar.gz"),
+        Some(Match {
+            params: smallvec!["12", "tar.gz"],
+            sub_route_path: None,
+            case_mismatch: false,
+        })
+    );
+    assert!(FILES.matches("/files/file-12.").is_none());
+    assert!(FILES.matches("/files/file-.json").is_none());
+    assert!(FILES.matches("/files/file-a.json").is_none());
+    assert!(FILES.matches("/files/my-file-12.json").is_none());
+}
+
+#[test]
+fn test_pattern_matches_case_insensitive() {
+    static ABOUT: Pattern = Pattern {
+        components: &[
+            PatternComponent::Static("about"),
+            PatternComponent::Param(None),
+        ],
+        sub_route: false,
+        catch_all: false,
+        trailing_slash: false,
+        case_insensitive: true,
+    };
+    assert_eq!(
+        ABOUT
+            .matches("/about/Team")
+            .map(|match_| match_.case_mismatch),
+        Some(false)
+    );
+    assert_eq!(
+        ABOUT
+            .matches("/ABOUT/Team")
+            .map(|match_| (match_.params.to_vec(), match_.case_mismatch)),
+        Some((vec!["Team"], true))
+    );
+    assert!(ABOUT.matches("/abouts/Team").is_none());
+}
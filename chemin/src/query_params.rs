@@ -0,0 +1,376 @@
+use percent_encoding::NON_ALPHANUMERIC;
+use qstring::QString;
+
+/// How the query string parameters which aren't claimed by any field of the route (with `#[query_param]`) are handled when parsing a
+/// url, defined for an enum with `#[chemin(unknown_query = "...")]`.
+///
+/// The query string is shared by the whole route tree, so the policy of the type on which the url is parsed applies, and the
+/// policies of its sub-route types are not used.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub enum UnknownQuery {
+    /// The unknown query string parameters are ignored, and lost when generating the url of the route again. This is the default
+    /// policy.
+    #[default]
+    Ignore,
+    /// A url containing an unknown query string parameter doesn't correspond to any route
+    /// ([ParseError::UnknownQueryParam](crate::ParseError::UnknownQueryParam)).
+    Reject,
+    /// The unknown query string parameters are collected, in order, by the `#[query_rest]` field of the route (or of one of its
+    /// sub-routes), and added back when generating the url of the route. They are ignored if there is no such field.
+    Capture,
+}
+
+/// The order of the query string parameters in the generated urls, defined for an enum with `#[chemin(query_order = "...")]`.
+///
+/// Like [UnknownQuery], the order of the type on which the url is generated applies to the whole route tree. Except with
+/// [QueryOrder::Alphabetical], the values of a repeated query string parameter keep their order, so collections with an unspecified
+/// iteration order (such as [HashSet](std::collections::HashSet)) generate their values of `#[query_param(multiple)]` in an
+/// unspecified order.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub enum QueryOrder {
+    /// The query string parameters of a route come first, in the order of its fields, followed by those of its sub-route. This is
+    /// the default order.
+    #[default]
+    Tree,
+    /// The query string parameters are in the order of the fields of the route, those of its sub-route being at the position of the
+    /// sub-route field.
+    Declaration,
+    /// The query string parameters are sorted by key, and then by value (including the values joined with the separator of
+    /// `#[query_param(multiple, separator = "...")]`). Equal routes always generate byte-identical urls, whatever the iteration
+    /// order of their collections, and whatever the structure of their route tree as long as the keys are the same.
+    Alphabetical,
+}
+
+/// Returns the value of the query string parameter whose key is the first of `keys` (the primary key, followed by the aliases)
+/// present in `qstring`.
+#[doc(hidden)]
+pub fn query_param_value<'a>(qstring: &'a QString, keys: &[&str]) -> Option<&'a str> {
+    keys.iter().find_map(|key| qstring.get(key))
+}
+
+/// Returns the values of the query string parameter declared with `#[query_param(multiple)]` whose keys are `keys` (the primary
+/// key, followed by the aliases), in the order of the query string. With a `separator`, each value is split, except for an empty
+/// value (as in `ids=`), which gives no value at all. The empty values around a separator (as in `ids=1,,2,`) are kept.
+#[doc(hidden)]
+pub fn query_param_values<'a>(
+    qstring: &'a QString,
+    keys: &[&str],
+    separator: Option<&'a str>,
+) -> Vec<&'a str> {
+    let values = qstring
+        .to_pairs()
+        .into_iter()
+        .filter(|(key, _)| keys.contains(key))
+        .map(|(_, value)| value);
+
+    match separator {
+        Some(separator) => values
+            .filter(|value| !value.is_empty())
+            .flat_map(|value| value.split(separator))
+            .collect(),
+        None => values.collect(),
+    }
+}
+
+/// Adds the values of a query string parameter declared with `#[query_param(multiple)]` to `qstring`: one pair per value, or a single
+/// pair joining them with the `separator` if there is one (and no pair if there is no value).
+///
+/// Returns the first value containing the `separator` as an error, since it couldn't be parsed back (nothing is added then).
+///
+/// With [QueryOrder::Alphabetical], the values joined with the `separator` are sorted (the pairs being sorted afterwards by
+/// [sort_query_params]).
+#[doc(hidden)]
+pub fn add_query_param_values(
+    qstring: &mut QString,
+    name: &str,
+    values: impl Iterator<Item = String>,
+    separator: Option<&str>,
+    query_order: QueryOrder,
+) -> Result<(), String> {
+    match separator {
+        Some(separator) => {
+            let mut values = values.collect::<Vec<_>>();
+
+            if query_order == QueryOrder::Alphabetical {
+                values.sort_unstable();
+            }
+
+            if let Some(value) = values.iter().find(|value| value.contains(separator)) {
+                return Err(value.clone());
+            }
+
+            if !values.is_empty() {
+                qstring.add_pair((name, values.join(separator)));
+            }
+        }
+
+        None => {
+            for value in values {
+                qstring.add_pair((name, value));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the query string parameters of `qstring` whose keys start with `prefix`, without it: the query string seen by a sub-route
+/// declared with `query_prefix = "..."`.
+#[doc(hidden)]
+pub fn strip_query_prefix(qstring: &QString, prefix: &str) -> QString {
+    QString::new(strip_query_prefix_from_pairs(qstring.to_pairs(), prefix))
+}
+
+/// Returns the pairs whose keys start with `prefix`, without it, in order.
+#[doc(hidden)]
+pub fn strip_query_prefix_from_pairs<K: AsRef<str>, V>(
+    pairs: Vec<(K, V)>,
+    prefix: &str,
+) -> Vec<(String, V)> {
+    pairs
+        .into_iter()
+        .filter_map(|(key, value)| {
+            key.as_ref()
+                .strip_prefix(prefix)
+                .map(|key| (key.to_owned(), value))
+        })
+        .collect()
+}
+
+/// Adds the query string parameters generated by a sub-route declared with `query_prefix = "..."` to `qstring`, prefixing their
+/// keys with `prefix`.
+#[doc(hidden)]
+pub fn add_query_prefix(qstring: &mut QString, sub_route_qstring: QString, prefix: &str) {
+    for (key, value) in into_pairs_with_flags(sub_route_qstring) {
+        add_pair_or_flag(qstring, &format!("{}{}", prefix, key), value);
+    }
+}
+
+/// Moves the query string parameters added by a sub-route (from `sub_route_start` to the end of `qstring`) to `index`, the
+/// position of the sub-route field among the query string parameters of its parent route, for [QueryOrder::Declaration].
+#[doc(hidden)]
+pub fn move_sub_route_query_params(qstring: &mut QString, index: usize, sub_route_start: usize) {
+    let mut pairs = into_pairs_with_flags(std::mem::take(qstring));
+    let sub_route_len = pairs.len() - sub_route_start;
+    pairs[index..].rotate_right(sub_route_len);
+    *qstring = from_pairs_with_flags(pairs);
+}
+
+/// Sorts the query string parameters of `qstring` by key, and then by value, for [QueryOrder::Alphabetical].
+pub(crate) fn sort_query_params(qstring: &mut QString) {
+    let mut pairs = into_pairs_with_flags(std::mem::take(qstring));
+    pairs.sort_unstable();
+    *qstring = from_pairs_with_flags(pairs);
+}
+
+/// Parses the value of a query string parameter declared with `#[query_param(flag)]`: `true` if it is present, whatever its value
+/// (as in `?debug`, `?debug=`, `?debug=1` or even `?debug=false`), and `false` if it is absent.
+#[doc(hidden)]
+pub fn parse_query_flag(value: Option<&str>) -> bool {
+    value.is_some()
+}
+
+/// Adds a query string parameter declared with `#[query_param(flag)]` to `qstring`, displayed without a value (as in `?debug`).
+#[doc(hidden)]
+pub fn add_query_flag(qstring: &mut QString, key: &str) {
+    // `QString::add_pair` always adds a value (displayed as `?debug=`), unlike the parsing of a pair without "=".
+    qstring.add_str(&percent_encoding::utf8_percent_encode(key, NON_ALPHANUMERIC).to_string());
+}
+
+/// Adds the query string parameters collected by a `#[query_rest]` field to `qstring`, those without a value (see
+/// [into_pairs_with_flags]) being added back without a value.
+#[doc(hidden)]
+pub fn add_query_rest(qstring: &mut QString, query_rest: &[(String, Option<String>)]) {
+    for (key, value) in query_rest {
+        add_pair_or_flag(qstring, key, value.clone());
+    }
+}
+
+/// Returns the pairs of `qstring`, where the value of a flag (see [add_query_flag]) is [None]. `QString` only exposes this
+/// distinction in its display, where a pair contains a "=" if and only if it has a value ("&" and "=" being encoded in the keys and
+/// the values).
+pub(crate) fn into_pairs_with_flags(qstring: QString) -> Vec<(String, Option<String>)> {
+    let display = qstring.to_string();
+
+    display
+        .split('&')
+        .zip(qstring.into_pairs())
+        .map(|(displayed_pair, (key, value))| (key, displayed_pair.contains('=').then_some(value)))
+        .collect()
+}
+
+/// The inverse of [into_pairs_with_flags].
+fn from_pairs_with_flags(pairs: Vec<(String, Option<String>)>) -> QString {
+    let mut qstring = QString::default();
+
+    for (key, value) in pairs {
+        add_pair_or_flag(&mut qstring, &key, value);
+    }
+
+    qstring
+}
+
+fn add_pair_or_flag(qstring: &mut QString, key: &str, value: Option<String>) {
+    match value {
+        Some(value) => qstring.add_pair((key, value)),
+        None => add_query_flag(qstring, key),
+    }
+}
+
+#[test]
+fn test_query_param_value() {
+    let qstring = QString::from("?per_page=10&pageSize=20&size=30");
+    assert_eq!(
+        query_param_value(&qstring, &["pageSize", "per_page"]),
+        Some("20")
+    );
+    assert_eq!(
+        query_param_value(&qstring, &["limit", "per_page"]),
+        Some("10")
+    );
+    assert_eq!(query_param_value(&qstring, &["limit"]), None);
+}
+
+#[test]
+fn test_query_param_values() {
+    let qstring = QString::from("?tag=a&page=2&tag=b%20c&tag=&ids=1,2&ids=&ids=3&t=d");
+    assert_eq!(
+        query_param_values(&qstring, &["tag"], None),
+        vec!["a", "b c", ""]
+    );
+    assert_eq!(
+        query_param_values(&qstring, &["tag", "t"], None),
+        vec!["a", "b c", "", "d"]
+    );
+    assert_eq!(
+        query_param_values(&qstring, &["ids"], Some(",")),
+        vec!["1", "2", "3"]
+    );
+    assert_eq!(
+        query_param_values(&qstring, &["missing"], None),
+        Vec::<&str>::new()
+    );
+
+    let qstring = QString::from("?ids=1,,2,&ids=");
+    assert_eq!(
+        query_param_values(&qstring, &["ids"], Some(",")),
+        vec!["1", "", "2", ""]
+    );
+
+    let mut qstring = QString::default();
+    assert_eq!(
+        add_query_param_values(
+            &mut qstring,
+            "tag",
+            vec![String::from("a"), String::from("b,c")].into_iter(),
+            None,
+            QueryOrder::Alphabetical,
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        add_query_param_values(
+            &mut qstring,
+            "ids",
+            vec![String::from("2"), String::from("1")].into_iter(),
+            Some(","),
+            QueryOrder::Tree,
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        add_query_param_values(
+            &mut qstring,
+            "sorted",
+            vec![String::from("b"), String::from("a")].into_iter(),
+            Some(","),
+            QueryOrder::Alphabetical,
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        add_query_param_values(
+            &mut qstring,
+            "empty",
+            Vec::new().into_iter(),
+            Some(","),
+            QueryOrder::Tree,
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        add_query_param_values(
+            &mut qstring,
+            "names",
+            vec![String::from("a"), String::from("b,c")].into_iter(),
+            Some(","),
+            QueryOrder::Tree,
+        ),
+        Err(String::from("b,c"))
+    );
+    assert_eq!(qstring.to_string(), "tag=a&tag=b,c&ids=2,1&sorted=a,b");
+}
+
+#[test]
+fn test_query_prefix() {
+    let qstring = QString::from("?page=1&list.page=2&list.sort=name&lists=3&list.=4");
+    assert_eq!(
+        strip_query_prefix(&qstring, "list.").to_pairs(),
+        vec![("page", "2"), ("sort", "name"), ("", "4")],
+    );
+
+    let mut qstring = QString::new(vec![("page", "1")]);
+    add_query_prefix(
+        &mut qstring,
+        QString::new(vec![("page", "2"), ("sort", "name")]),
+        "list.",
+    );
+    assert_eq!(
+        qstring.to_pairs(),
+        vec![("page", "1"), ("list.page", "2"), ("list.sort", "name")],
+    );
+}
+
+#[test]
+fn test_query_order() {
+    let mut qstring = QString::new(vec![("a", "1"), ("b", "2"), ("c", "3"), ("d", "4")]);
+    move_sub_route_query_params(&mut qstring, 1, 3);
+    assert_eq!(
+        qstring.to_pairs(),
+        vec![("a", "1"), ("d", "4"), ("b", "2"), ("c", "3")],
+    );
+
+    let mut qstring = QString::new(vec![("b", "2"), ("a", "1"), ("b", "1"), ("A", "0")]);
+    sort_query_params(&mut qstring);
+    assert_eq!(
+        qstring.to_pairs(),
+        vec![("A", "0"), ("a", "1"), ("b", "1"), ("b", "2")],
+    );
+}
+
+#[test]
+fn test_query_flag() {
+    assert!(!parse_query_flag(None));
+    assert!(parse_query_flag(Some("")));
+    assert!(parse_query_flag(Some("true")));
+    assert!(parse_query_flag(Some("1")));
+    assert!(parse_query_flag(Some("yes")));
+    assert!(parse_query_flag(Some("false")));
+    assert!(parse_query_flag(Some("0")));
+
+    let mut qstring = QString::new(vec![("b", "")]);
+    add_query_flag(&mut qstring, "debug mode");
+    add_query_flag(&mut qstring, "a");
+    assert_eq!(qstring.to_string(), "b=&debug%20mode&a");
+    assert_eq!(qstring.get("debug mode"), Some(""));
+
+    // The flags are kept when the query string parameters are moved.
+    sort_query_params(&mut qstring);
+    assert_eq!(qstring.to_string(), "a&b=&debug%20mode");
+    move_sub_route_query_params(&mut qstring, 0, 2);
+    assert_eq!(qstring.to_string(), "debug%20mode&a&b=");
+
+    let mut prefixed_qstring = QString::default();
+    add_query_prefix(&mut prefixed_qstring, qstring, "x.");
+    assert_eq!(prefixed_qstring.to_string(), "x.debug%20mode&x.a&x.b=");
+}
@@ -0,0 +1,251 @@
+use crate::{AcceptedLocales, Locale, RouteLocales, TrailingSlash};
+
+/// The description of a route, that is to say of a variant of an enum deriving [Chemin](crate::Chemin). See
+/// [Chemin::ROUTES](crate::Chemin::ROUTES).
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct RouteInfo {
+    /// The name of the variant.
+    pub variant: &'static str,
+    /// The paths of the route, one for each group of locales.
+    pub paths: &'static [PathInfo],
+    /// The url parameters, in the order of the fields of the variant.
+    pub params: &'static [ParamInfo],
+    /// The query string parameters, in the order of the fields of the variant.
+    pub query_params: &'static [QueryParamInfo],
+    /// The name of the field collecting the unknown query string parameters, declared with `#[query_rest]`, if there is one.
+    pub query_rest: Option<&'static str>,
+    /// The sub-route, if there is one.
+    pub sub_route: Option<SubRouteInfo>,
+    /// The trailing slash policy of the enum, defined with `#[chemin(trailing_slash = "...")]`.
+    pub trailing_slash: TrailingSlash,
+}
+
+/// A path of a route. See [RouteInfo].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct PathInfo {
+    /// The path, with the syntax of the `#[route]` attribute (for example `"/hello/:name/..sub_route"`).
+    pub pattern: &'static str,
+    /// The locales of this path, sorted. It is empty if this path is not specific to any locale.
+    pub locales: &'static [Locale],
+}
+
+/// A url parameter of a route. See [RouteInfo].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ParamInfo {
+    /// The name of the field. For unnamed fields, it is the index of the field (`"0"`, `"1"`, ...).
+    pub name: &'static str,
+    /// The type of the field, as written in the enum.
+    pub ty: &'static str,
+}
+
+/// A query string parameter of a route. See [RouteInfo].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct QueryParamInfo {
+    /// The name of the field.
+    pub name: &'static str,
+    /// The key of the query string parameter: the name of the field, or the one defined with `rename = "..."`. It is the key used
+    /// when generating a url.
+    pub key: &'static str,
+    /// The other keys accepted when parsing a url, defined with `alias = "..."`.
+    pub aliases: &'static [&'static str],
+    /// The type of the field, as written in the enum.
+    pub ty: &'static str,
+    /// How the query string parameter is declared.
+    pub kind: QueryParamKind,
+}
+
+/// How a query string parameter is declared. See [QueryParamInfo].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum QueryParamKind {
+    /// `#[query_param]`
+    Mandatory,
+    /// `#[query_param(optional)]`
+    Optional,
+    /// `#[query_param(default = ...)]`. Contains the default value expression, as written in the enum.
+    WithDefaultValue(&'static str),
+    /// `#[query_param(multiple)]`. Contains the separator defined with `separator = "..."`, if there is one.
+    Multiple(Option<&'static str>),
+    /// `#[query_param(flatten)]`. The field is parsed from all the query string parameters, so [QueryParamInfo::key] is only the name
+    /// of the field.
+    Flatten,
+    /// `#[query_param(flag)]`
+    Flag,
+}
+
+/// The sub-route of a route. See [RouteInfo].
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SubRouteInfo {
+    /// The name of the field. For unnamed fields, it is the index of the field (`"0"`, `"1"`, ...).
+    pub name: &'static str,
+    /// The type of the field, as written in the enum.
+    pub ty: &'static str,
+    /// The routes of the sub-route type.
+    pub routes: &'static [RouteInfo],
+    /// The prefix of the query string parameter keys of the sub-route, defined with `#[route("...", query_prefix = "...")]`, if there
+    /// is one.
+    pub query_prefix: Option<&'static str>,
+}
+
+/// A route of the flattened route tree, as visited by [Chemin::walk_routes](crate::Chemin::walk_routes).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct FlatRoute {
+    /// The chain of routes leading to this one, from the outermost one (a variant of the type on which
+    /// [walk_routes](crate::Chemin::walk_routes) was called) to the innermost one (which has no sub-route).
+    pub routes: Vec<&'static RouteInfo>,
+    /// The complete path, where the sub-routes are replaced by their own paths (for example `"/with-sub-route/bonjour"`).
+    pub pattern: String,
+    /// The locales of the complete path, obtained by intersecting the locales of each level, sorted. It is empty if the complete path
+    /// is not specific to any locale.
+    pub locales: Vec<Locale>,
+}
+
+/// Returns whether a query string parameter of `routes`, or of their sub-routes, uses `key` as its key or as one of its aliases. The
+/// query prefixes of the sub-routes are taken into account.
+#[doc(hidden)]
+pub const fn route_tree_uses_query_key(routes: &[RouteInfo], key: &str) -> bool {
+    route_tree_uses_query_key_bytes(routes, key.as_bytes())
+}
+
+/// [route_tree_uses_query_key], on bytes, since they can be sliced in const functions.
+const fn route_tree_uses_query_key_bytes(routes: &[RouteInfo], key: &[u8]) -> bool {
+    let mut i = 0;
+
+    while i < routes.len() {
+        let route = &routes[i];
+        let mut j = 0;
+
+        while j < route.query_params.len() {
+            let query_param = &route.query_params[j];
+
+            // A flattened query param has no key of its own.
+            if matches!(query_param.kind, QueryParamKind::Flatten) {
+                j += 1;
+                continue;
+            }
+
+            if bytes_eq(query_param.key.as_bytes(), key) {
+                return true;
+            }
+
+            let mut k = 0;
+
+            while k < query_param.aliases.len() {
+                if bytes_eq(query_param.aliases[k].as_bytes(), key) {
+                    return true;
+                }
+
+                k += 1;
+            }
+
+            j += 1;
+        }
+
+        if let Some(sub_route) = &route.sub_route {
+            let sub_route_key = match sub_route.query_prefix {
+                Some(query_prefix) => {
+                    let query_prefix = query_prefix.as_bytes();
+
+                    if key.len() >= query_prefix.len() {
+                        let (key_prefix, key_rest) = key.split_at(query_prefix.len());
+
+                        if bytes_eq(key_prefix, query_prefix) {
+                            Some(key_rest)
+                        } else {
+                            None
+                        }
+                    } else {
+                        None
+                    }
+                }
+
+                None => Some(key),
+            };
+
+            if let Some(sub_route_key) = sub_route_key {
+                if route_tree_uses_query_key_bytes(sub_route.routes, sub_route_key) {
+                    return true;
+                }
+            }
+        }
+
+        i += 1;
+    }
+
+    false
+}
+
+/// `a == b`, usable in const functions.
+const fn bytes_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    let mut i = 0;
+
+    while i < a.len() {
+        if a[i] != b[i] {
+            return false;
+        }
+
+        i += 1;
+    }
+
+    true
+}
+
+pub(crate) fn walk_routes(
+    routes: &'static [RouteInfo],
+    parents: &mut Vec<&'static RouteInfo>,
+    parent_pattern: &str,
+    accepted_locales: &AcceptedLocales,
+    visitor: &mut impl FnMut(&FlatRoute),
+) {
+    for route in routes {
+        parents.push(route);
+
+        for path in route.paths {
+            let route_locales = if path.locales.is_empty() {
+                RouteLocales::Any
+            } else {
+                RouteLocales::Some(path.locales)
+            };
+
+            if !accepted_locales.accept(&route_locales) {
+                // This path can't be reached from the parent route.
+                continue;
+            }
+
+            match &route.sub_route {
+                Some(sub_route) => {
+                    // The sub-route is always the last component of the path.
+                    let pattern = format!(
+                        "{}{}",
+                        parent_pattern,
+                        &path.pattern[..path.pattern.rfind("/..").unwrap()],
+                    );
+
+                    walk_routes(
+                        sub_route.routes,
+                        parents,
+                        &pattern,
+                        &accepted_locales.accepted_locales_for_sub_route(&route_locales),
+                        visitor,
+                    );
+                }
+
+                None => {
+                    let mut locales = accepted_locales.resulting_locales(&route_locales);
+                    locales.sort_unstable();
+
+                    visitor(&FlatRoute {
+                        routes: parents.clone(),
+                        pattern: format!("{}{}", parent_pattern, path.pattern),
+                        locales,
+                    });
+                }
+            }
+        }
+
+        parents.pop();
+    }
+}
@@ -0,0 +1,81 @@
+use crate::{Locale, ParseError};
+
+/// How the trailing slash of a url is handled when parsing it, defined for an enum with `#[chemin(trailing_slash = "...")]`.
+///
+/// The policy of an enum applies to its own routes: when a route has a sub-route, the policy of the sub-route type is the one used
+/// for the end of the url.
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash, Debug)]
+pub enum TrailingSlash {
+    /// `"/about"` and `"/about/"` are distinct urls: a url only corresponds to a route if its trailing slash matches the path of the
+    /// route. This is the default policy.
+    #[default]
+    Strict,
+    /// A url whose trailing slash doesn't match the path of any route corresponds to the route it would match with (or without) a
+    /// trailing slash.
+    Ignore,
+    /// Same as [TrailingSlash::Strict], but [Chemin::parse_or_redirect](crate::Chemin::parse_or_redirect) returns the url with (or
+    /// without) a trailing slash, if it corresponds to a route.
+    Redirect,
+}
+
+/// The result of [Chemin::parse_or_redirect](crate::Chemin::parse_or_redirect).
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum ParseOrRedirect<T> {
+    /// The url corresponds to this route, for these locales (see [Chemin::parse](crate::Chemin::parse)).
+    Route(T, Vec<Locale>),
+    /// The url corresponds to a route, but isn't canonical (for example because its trailing slash doesn't match the path of a route
+    /// whose trailing slash policy is [TrailingSlash::Redirect]). Contains the canonical url, to which the client should be
+    /// redirected.
+    Redirect(String),
+}
+
+/// Parses `path` with `parse`, and if no route corresponds to it, tries again by toggling its trailing slash, according to `policy`.
+#[doc(hidden)]
+pub fn parse_with_trailing_slash<T>(
+    path: &str,
+    policy: TrailingSlash,
+    parse: impl Fn(&str) -> Result<T, ParseError>,
+) -> Result<T, ParseError> {
+    match (parse(path), policy) {
+        (Err(ParseError::NotFound), TrailingSlash::Ignore) => match toggle_trailing_slash(path) {
+            Some(toggled_path) => parse(&toggled_path),
+            None => Err(ParseError::NotFound),
+        },
+
+        (Err(ParseError::NotFound), TrailingSlash::Redirect) => match toggle_trailing_slash(path) {
+            Some(toggled_path) => {
+                parse(&toggled_path).and_then(|_| Err(ParseError::TrailingSlashMismatch))
+            }
+            None => Err(ParseError::NotFound),
+        },
+
+        (result, _) => result,
+    }
+}
+
+/// Removes the trailing slash of `path` if it has one, or adds one if it doesn't. Returns [None] for the root path, which always
+/// has a slash.
+pub(crate) fn toggle_trailing_slash(path: &str) -> Option<String> {
+    if path.is_empty() || path == "/" {
+        None
+    } else if let Some(path) = path.strip_suffix('/') {
+        Some(String::from(path))
+    } else {
+        Some(format!("{}/", path))
+    }
+}
+
+#[test]
+fn test_toggle_trailing_slash() {
+    assert_eq!(toggle_trailing_slash(""), None);
+    assert_eq!(toggle_trailing_slash("/"), None);
+    assert_eq!(
+        toggle_trailing_slash("/about"),
+        Some(String::from("/about/"))
+    );
+    assert_eq!(
+        toggle_trailing_slash("/about/"),
+        Some(String::from("/about"))
+    );
+    assert_eq!(toggle_trailing_slash("about"), Some(String::from("about/")));
+}